@@ -0,0 +1,70 @@
+//! Benchmarks for `detect`. Throughput is reported in input ranges
+//! per second.
+//!
+//! To compare the serial and the parallel implementation, save a
+//! baseline without the `rayon` feature and compare against it with
+//! the feature enabled:
+//!
+//! ```sh
+//! cargo bench --bench detect -- --save-baseline serial
+//! cargo bench --bench detect --features rayon -- --baseline serial
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use unicode_language::{detect, Codepoint, Range};
+
+/// Returns `count` single codepoint ranges spread evenly across the
+/// Basic Multilingual Plane.
+fn scattered(count: u32) -> Vec<Range<Codepoint>> {
+    let step = 0x10000 / count;
+    (0..count).map(|i| [i * step, i * step]).collect()
+}
+
+/// Returns `count` ranges resembling the `cmap` of a font, with runs
+/// of 1 to 16 codepoints separated by small gaps, starting at the
+/// Basic Latin block.
+fn font(count: u32) -> Vec<Range<Codepoint>> {
+    let mut lower = 0x20;
+    (0..count)
+        .map(|i| {
+            let upper = lower + i % 16;
+            let range = [lower, upper];
+            lower = upper + 2 + i % 5;
+            range
+        })
+        .collect()
+}
+
+/// Returns the `cmap` of DejaVu Sans 2.37, as sorted, coalesced
+/// ranges. It covers 5918 codepoints in 281 ranges across Latin,
+/// Greek, Cyrillic, Armenian, Hebrew, Arabic and many symbol blocks.
+fn dejavu_sans() -> Vec<Range<Codepoint>> {
+    serde_json::from_str(include_str!("fixtures/dejavu-sans.json")).unwrap()
+}
+
+fn bench_detect(c: &mut Criterion) {
+    let mut group = c.benchmark_group("detect");
+
+    let inputs = [
+        ("basic latin", vec![[0x41, 0x5A], [0x61, 0x7A]]),
+        ("full bmp", vec![[0x0000, 0xFFFF]]),
+        ("font", font(400)),
+        ("dejavu sans", dejavu_sans()),
+        ("scattered ranges", scattered(4096)),
+        ("many scattered ranges", scattered(32768)),
+    ];
+
+    for (name, input) in inputs {
+        group.throughput(Throughput::Elements(input.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| detect(black_box(input.iter().copied()), black_box(0.5)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_detect);
+criterion_main!(benches);
@@ -1,117 +1,310 @@
 use std::env;
-use std::fs::read_to_string;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
+use std::process::Command;
 
 use glob::glob;
-use langtag::LanguageTag;
-use serde::{de::Error, Deserialize, Deserializer};
-
-#[derive(Clone, Debug, PartialEq)]
-struct Range(u32, u32);
-
-#[derive(Debug, Deserialize)]
-struct Language {
-    anglicized_name: String,
-    native_name: String,
-    codepoints: Vec<Range>,
-    tag: Option<String>,
-}
+
+#[path = "build/languages.rs"]
+mod languages;
+
+use languages::{
+    check_duplicate_tags, intern, language_tag, parse_language, remove_empty, total, Language,
+    Range,
+};
 
 #[derive(Debug)]
 pub struct Metadata {
     pub tag: String,
     pub name: String,
     pub native_name: String,
+    pub script: String,
+    pub speakers: Option<u64>,
 }
 
-impl<'l> Deserialize<'l> for Range {
-    fn deserialize<T>(deserializer: T) -> Result<Self, T::Error>
-    where
-        T: Deserializer<'l>,
-    {
-        let s: &str = Deserialize::deserialize(deserializer)?;
-
-        if s.contains("..") {
-            s.split("..")
-                .map(|x| x.parse::<u32>())
-                .collect::<Result<Vec<_>, _>>()
-                .map(|v| Range(v[0], v[1]))
-                .map_err(T::Error::custom)
-        } else {
-            s.parse::<u32>()
-                .map(|i| Range(i, i))
-                .map_err(T::Error::custom)
-        }
-    }
+/// The `script-*` features and the ISO 15924 codes of the scripts they
+/// select.
+const SCRIPT_FEATURES: &[(&str, &str)] = &[
+    ("SCRIPT_ARABIC", "Arab"),
+    ("SCRIPT_CYRILLIC", "Cyrl"),
+    ("SCRIPT_DEVANAGARI", "Deva"),
+    ("SCRIPT_GREEK", "Grek"),
+    ("SCRIPT_HEBREW", "Hebr"),
+    ("SCRIPT_LATIN", "Latn"),
+];
+
+/// Returns the scripts selected by the enabled `script-*` features.
+fn selected_scripts() -> Vec<&'static str> {
+    SCRIPT_FEATURES
+        .iter()
+        .filter(|(feature, _)| env::var_os(format!("CARGO_FEATURE_{feature}")).is_some())
+        .map(|(_, script)| *script)
+        .collect()
 }
 
-fn parse_yaml<T: AsRef<Path>>(path: T) -> Language {
+/// Reruns the build script when a file or directory changes.
+///
+/// Cargo treats a missing path as always changed, which would rerun the
+/// script and recompile the generated data on every build, so missing
+/// paths, such as an uninitialized submodule, are left out.
+fn rerun_if_changed<P: AsRef<Path>>(path: P) {
     let path = path.as_ref();
 
-    let s = read_to_string(path).unwrap();
+    if path.exists() {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+}
+
+/// Returns the version of the data in a directory.
+///
+/// The version is read from a `VERSION` file next to the directory,
+/// such as `speakeasy/VERSION`, or else taken from `git describe` of
+/// the repository whose top level is next to the directory, which is
+/// the speakeasy submodule by default. A repository further up, such as
+/// one the crate is vendored into, is not used. Returns `unknown` if
+/// neither is available.
+fn data_version(data_dir: &str) -> String {
+    let root = Path::new(data_dir).parent().unwrap_or(Path::new("."));
+    let version_file = root.join("VERSION");
+    rerun_if_changed(&version_file);
+
+    if let Ok(version) = std::fs::read_to_string(&version_file) {
+        return version.trim().to_string();
+    }
+
+    let is_data_repository = git(data_dir, &["rev-parse", "--show-toplevel"])
+        .zip(root.canonicalize().ok())
+        .is_some_and(|(top, root)| Path::new(&top) == root);
+
+    if !is_data_repository {
+        return "unknown".to_string();
+    }
 
-    // The Serde YAML parser expects YAML types to have names that are valid
-    // Rust identifiers. Sadly, that is not the case here, so we manually perform
-    // a string replace to patch up the data.
-    let mut d: Language = serde_yml::from_str(&s.replace("ruby/range", "Range")).unwrap();
+    // A new commit or tag changes the output of `git describe`. The
+    // HEAD of a submodule is usually detached, but a checked out branch
+    // moves its own ref instead.
+    if let Some(git_dir) = git(data_dir, &["rev-parse", "--absolute-git-dir"]) {
+        let git_dir = Path::new(&git_dir);
+        rerun_if_changed(git_dir.join("HEAD"));
+        rerun_if_changed(git_dir.join("refs/tags"));
 
-    d.tag = Some(
-        path.file_name()
-            .unwrap()
-            .to_os_string()
-            .into_string()
-            .unwrap(),
-    );
+        if let Some(branch) = git(data_dir, &["symbolic-ref", "-q", "HEAD"]) {
+            rerun_if_changed(git_dir.join(branch));
+        }
+    }
 
-    // Sort the ranges so we can exit early when running the detection code.
-    d.codepoints.sort_by_key(|c| c.0);
+    git(data_dir, &["describe", "--tags", "--always", "--dirty"])
+        .unwrap_or_else(|| "unknown".to_string())
+}
 
-    d
+/// Runs a git command in a directory and returns its trimmed output,
+/// or `None` if it fails or prints nothing.
+fn git(dir: &str, args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|output| output.trim().to_string())
+        .filter(|output| !output.is_empty())
 }
 
 fn main() {
-    let languages: Vec<Language> = glob("./speakeasy/data/*")
+    // Additional languages can be supplied as JSON files in `data`.
+    // The bundled speakeasy data can be replaced by another directory.
+    println!("cargo:rerun-if-env-changed=UNICODE_LANGUAGE_DATA_DIR");
+    let data_dir =
+        env::var("UNICODE_LANGUAGE_DATA_DIR").unwrap_or_else(|_| "./speakeasy/data".to_string());
+
+    // Printing any `rerun-if` line disables the default of rerunning on
+    // every change to the package, so the inputs are listed explicitly.
+    rerun_if_changed("build.rs");
+    rerun_if_changed("build");
+    rerun_if_changed(&data_dir);
+    rerun_if_changed("data");
+
+    // Both directories are searched recursively, so the files can be
+    // organized in subdirectories.
+    let paths = glob(&format!("{data_dir}/**/*"))
         .unwrap()
+        .chain(glob("./data/**/*.json").unwrap())
         .map(Result::unwrap)
-        .map(parse_yaml)
-        .filter(|l| LanguageTag::parse(l.tag.as_ref().unwrap()).is_ok())
+        .filter(|path| path.is_file());
+
+    // Collect the errors of all files so they can be fixed in one go.
+    let mut errors = Vec::new();
+    let mut languages: Vec<Language> = Vec::new();
+
+    for path in paths {
+        // Files not named after a language tag, such as a README, are
+        // not language data and are skipped.
+        if let Err(warning) = language_tag(&path) {
+            println!("cargo:warning={warning}, skipping");
+            continue;
+        }
+
+        match parse_language(path) {
+            Ok(language) => languages.push(language),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    let (languages, warnings) = remove_empty(languages);
+    for warning in warnings {
+        println!("cargo:warning={warning}");
+    }
+
+    if let Err(duplicates) = check_duplicate_tags(&languages) {
+        errors.extend(duplicates);
+    }
+
+    if !errors.is_empty() {
+        for error in &errors {
+            println!("cargo:warning={error}");
+        }
+        panic!(
+            "{} errors in the language data:\n{}",
+            errors.len(),
+            errors.join("\n")
+        );
+    }
+
+    // Only keep the languages written in the selected scripts, if any.
+    let scripts = selected_scripts();
+    let languages: Vec<Language> = languages
+        .into_iter()
+        .filter(|l| scripts.is_empty() || scripts.contains(&l.script.as_deref().unwrap()))
         .collect();
 
     let ranges: Vec<Vec<Range>> = languages.iter().map(|l| l.codepoints.to_vec()).collect();
-    let totals: Vec<u32> = ranges
+
+    for (language, ranges) in languages.iter().zip(&ranges) {
+        assert!(
+            ranges.windows(2).all(|w| w[0].1.saturating_add(1) < w[1].0),
+            "language {} has overlapping or adjacent ranges",
+            language.tag.as_ref().unwrap()
+        );
+    }
+    let totals: Vec<u32> = ranges.iter().map(|ranges| total(ranges)).collect();
+
+    // Most languages have no auxiliary codepoints, so these are always
+    // compiled as array literals, even with the `bincode` feature. They
+    // are only used with the `alloc` feature.
+    let aux_totals: Vec<u32> = languages.iter().map(|l| total(&l.auxiliary)).collect();
+    let aux_ranges_str = languages
         .iter()
-        .map(|ranges| ranges.iter().map(|c| c.1 - c.0 + 1).sum::<u32>())
-        .collect();
+        .map(|l| {
+            let ranges = l
+                .auxiliary
+                .iter()
+                .map(|c| format!("[{}, {}]", c.0, c.1))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("\n    // {}\n    &[{ranges}],", l.tag.as_ref().unwrap())
+        })
+        .collect::<String>();
+
+    // Native names are left empty to reduce the size of the embedded data.
+    let native_names = env::var_os("CARGO_FEATURE_NO_NATIVE_NAMES").is_none();
 
     let metadata: Vec<Metadata> = languages
         .into_iter()
         .map(|l| Metadata {
             tag: l.tag.as_ref().unwrap().clone(),
             name: l.anglicized_name.clone(),
-            native_name: l.native_name.clone(),
+            native_name: if native_names {
+                l.native_name.as_ref().unwrap().clone()
+            } else {
+                String::new()
+            },
+            script: l.script.as_ref().unwrap().clone(),
+            speakers: l.speakers,
         })
         .collect();
 
     let language_count = ranges.len();
+    let data_version = data_version(&data_dir);
 
-    let ranges_str = ranges
+    // Each entry is preceded by a comment naming its language, so the
+    // generated data can be navigated when debugging.
+    let metadata_str = metadata
         .iter()
-        .map(|ranges| {
-            format!(
-                "&[{}]",
-                ranges
+        .map(|m| format!("\n    // {}: {}\n    {m:?},", m.tag, m.name))
+        .collect::<String>();
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    // With the `bincode` feature the ranges are written to a binary file that
+    // is decoded on first use, instead of being compiled as array literals,
+    // unless the `const-data` feature needs them as constants. The array
+    // literals are then only compiled in tests, to check that the binary
+    // file decodes to the same ranges.
+    let binary = cfg!(all(feature = "bincode", not(feature = "const-data")));
+
+    #[cfg(all(feature = "bincode", not(feature = "const-data")))]
+    {
+        let ranges: Vec<Vec<[u32; 2]>> = ranges
+            .iter()
+            .map(|ranges| ranges.iter().map(|c| [c.0, c.1]).collect())
+            .collect();
+        let data = bincode::serialize(&ranges).unwrap();
+        std::fs::write(Path::new(&out_dir).join("ranges.bin"), data).unwrap();
+    }
+
+    let (ranges_cfg, ranges_name) = if binary {
+        ("#[cfg(test)]", "CONST_RANGES")
+    } else {
+        ("#[cfg(not(test))]", "RANGES")
+    };
+
+    // Languages with identical ranges share a single constant to keep the
+    // generated data small.
+    let ranges_const = {
+        let (sets, indices) = intern(&ranges);
+
+        let sets_str = sets
+            .iter()
+            .enumerate()
+            .map(|(i, ranges)| {
+                let tags = metadata
                     .iter()
-                    .map(|c| format!("[{}, {}]", c.0, c.1))
+                    .zip(&indices)
+                    .filter(|(_, &index)| index == i)
+                    .map(|(m, _)| m.tag.as_str())
                     .collect::<Vec<_>>()
-                    .join(", ")
-            )
-        })
-        .collect::<Vec<_>>()
-        .join(", ");
+                    .join(", ");
+
+                format!(
+                    "/// The ranges of {tags}.\n{ranges_cfg}\nconst RANGES_{i}: &[Range<Codepoint>] = &[{}];\n",
+                    ranges
+                        .iter()
+                        .map(|c| format!("[{}, {}]", c.0, c.1))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+            .collect::<String>();
+        let ranges_str = indices
+            .iter()
+            .zip(&metadata)
+            .map(|(i, m)| format!("\n    // {}: {}\n    RANGES_{i},", m.tag, m.name))
+            .collect::<String>();
+
+        let binary_const = if binary {
+            "\n#[cfg(not(test))]\nconst RANGES: binary::Ranges = binary::Ranges;"
+        } else {
+            ""
+        };
+
+        format!(
+            "{sets_str}\n{ranges_cfg}\nconst {ranges_name}: [&[Range<Codepoint>]; SUPPORTED_LANGUAGE_COUNT] = [{ranges_str}\n];{binary_const}"
+        )
+    };
 
-    let out_dir = env::var("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("data.rs");
     let mut f = File::create(dest_path).unwrap();
 
@@ -128,16 +321,32 @@ struct Metadata {{
     tag: &'static str,
     name: &'static str,
     native_name: &'static str,
+    script: &'static str,
+    speakers: Option<u64>,
 }}
 
+/// The number of languages compiled into the library.
+///
+/// Only languages whose tag is a valid BCP 47 language tag are
+/// included, as are only the languages selected by the `script-*`
+/// features if any are enabled.
+pub const SUPPORTED_LANGUAGE_COUNT: usize = {language_count};
+
+/// The version of the language data compiled into the library.
+///
+/// This is the content of the `VERSION` file next to the data
+/// directory, or else the `git describe` output of the data
+/// repository, such as the speakeasy submodule. It is `unknown` if
+/// neither was available at build time.
+pub const DATA_VERSION: &str = {data_version:?};
+
 #[cfg(not(test))]
-const LANGUAGE_COUNT: usize = {language_count};
+const LANGUAGE_COUNT: usize = SUPPORTED_LANGUAGE_COUNT;
 
 #[cfg(test)]
 const LANGUAGE_COUNT: usize = 5;
 
-#[cfg(not(test))]
-const RANGES: [&[Range<Codepoint>]; LANGUAGE_COUNT] = [{ranges_str}];
+{ranges_const}
 
 #[cfg(test)]
 const RANGES: [&[Range<Codepoint>]; LANGUAGE_COUNT] = [&[[1, 3]], &[[4, 6]], &[[7, 9]], &[[8, 8]], &[[16,16]]];
@@ -148,16 +357,30 @@ const TOTALS: [u32; LANGUAGE_COUNT] = {totals:?};
 #[cfg(test)]
 const TOTALS: [u32; LANGUAGE_COUNT] = [3, 3, 3, 1, 1];
 
+#[cfg(all(feature = "alloc", not(test)))]
+const AUX_RANGES: [&[Range<Codepoint>]; LANGUAGE_COUNT] = [{aux_ranges_str}
+];
+
+#[cfg(all(feature = "alloc", test))]
+const AUX_RANGES: [&[Range<Codepoint>]; LANGUAGE_COUNT] = [&[[10, 11]], &[], &[], &[], &[]];
+
+#[cfg(all(feature = "alloc", not(test)))]
+const AUX_TOTALS: [u32; LANGUAGE_COUNT] = {aux_totals:?};
+
+#[cfg(all(feature = "alloc", test))]
+const AUX_TOTALS: [u32; LANGUAGE_COUNT] = [2, 0, 0, 0, 0];
+
 #[cfg(not(test))]
-const METADATA: [Metadata; LANGUAGE_COUNT] = {metadata:?};
+const METADATA: [Metadata; LANGUAGE_COUNT] = [{metadata_str}
+];
 
 #[cfg(test)]
 const METADATA: [Metadata; LANGUAGE_COUNT] = [
-  Metadata {{ tag: "t1", name: "test1", native_name: "ntest1" }},
-  Metadata {{ tag: "t2", name: "test2", native_name: "ntest2" }},
-  Metadata {{ tag: "t3", name: "test3", native_name: "ntest3" }},
-  Metadata {{ tag: "t4", name: "test4", native_name: "ntest4" }},
-  Metadata {{ tag: "t5", name: "test5", native_name: "ntest5" }},
+  Metadata {{ tag: "t1", name: "test1", native_name: "ntest1", script: "Latn", speakers: Some(100) }},
+  Metadata {{ tag: "t2", name: "test2", native_name: "ntest2", script: "Latn", speakers: None }},
+  Metadata {{ tag: "t3", name: "test3", native_name: "ntest3", script: "Cyrl", speakers: Some(1000) }},
+  Metadata {{ tag: "t4", name: "test4", native_name: "ntest4", script: "Cyrl", speakers: None }},
+  Metadata {{ tag: "t5", name: "test5", native_name: "ntest5", script: "Grek", speakers: Some(10) }},
 ];
 "#
     )
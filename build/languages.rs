@@ -0,0 +1,427 @@
+//! Parsing of the language data, shared by the build script and its
+//! tests.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use langtag::LanguageTag;
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Range(pub u32, pub u32);
+
+#[derive(Debug, Deserialize)]
+pub struct Language {
+    pub anglicized_name: String,
+    /// Defaults to the anglicized name if missing.
+    pub native_name: Option<String>,
+    pub codepoints: Vec<Range>,
+    /// Auxiliary codepoints, such as letters of loanwords, which count
+    /// for less than the main codepoints. Codepoints that are also main
+    /// codepoints are removed.
+    #[serde(default)]
+    pub auxiliary: Vec<Range>,
+    pub tag: Option<String>,
+    pub script: Option<String>,
+    /// The number of speakers, if known.
+    pub speakers: Option<u64>,
+    /// The file the language was parsed from.
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// The largest Unicode codepoint.
+const MAX_CODEPOINT: u32 = 0x10FFFF;
+
+/// The letters of common scripts, used to guess the script of a
+/// language that does not specify one.
+const SCRIPTS: &[(&str, u32, u32)] = &[
+    ("Latn", 0x0041, 0x005A),
+    ("Latn", 0x0061, 0x007A),
+    ("Latn", 0x00C0, 0x024F),
+    ("Latn", 0x1E00, 0x1EFF),
+    ("Grek", 0x0370, 0x03FF),
+    ("Grek", 0x1F00, 0x1FFF),
+    ("Cyrl", 0x0400, 0x052F),
+    ("Armn", 0x0530, 0x058F),
+    ("Hebr", 0x0590, 0x05FF),
+    ("Arab", 0x0600, 0x06FF),
+    ("Arab", 0x0750, 0x077F),
+    ("Syrc", 0x0700, 0x074F),
+    ("Thaa", 0x0780, 0x07BF),
+    ("Deva", 0x0900, 0x097F),
+    ("Beng", 0x0980, 0x09FF),
+    ("Guru", 0x0A00, 0x0A7F),
+    ("Gujr", 0x0A80, 0x0AFF),
+    ("Orya", 0x0B00, 0x0B7F),
+    ("Taml", 0x0B80, 0x0BFF),
+    ("Telu", 0x0C00, 0x0C7F),
+    ("Knda", 0x0C80, 0x0CFF),
+    ("Mlym", 0x0D00, 0x0D7F),
+    ("Sinh", 0x0D80, 0x0DFF),
+    ("Thai", 0x0E00, 0x0E7F),
+    ("Laoo", 0x0E80, 0x0EFF),
+    ("Tibt", 0x0F00, 0x0FFF),
+    ("Mymr", 0x1000, 0x109F),
+    ("Geor", 0x10A0, 0x10FF),
+    ("Hang", 0x1100, 0x11FF),
+    ("Ethi", 0x1200, 0x139F),
+    ("Cher", 0x13A0, 0x13FF),
+    ("Cans", 0x1400, 0x167F),
+    ("Khmr", 0x1780, 0x17FF),
+    ("Mong", 0x1800, 0x18AF),
+    ("Tfng", 0x2D30, 0x2D7F),
+    ("Hira", 0x3040, 0x309F),
+    ("Kana", 0x30A0, 0x30FF),
+    ("Bopo", 0x3100, 0x312F),
+    ("Hani", 0x4E00, 0x9FFF),
+    ("Yiii", 0xA000, 0xA4CF),
+    ("Vaii", 0xA500, 0xA63F),
+    ("Hang", 0xAC00, 0xD7AF),
+];
+
+/// Returns the ISO 15924 code of the script with the most letters in
+/// the given ranges, or `Zzzz` if none of the ranges contain letters.
+pub fn dominant_script(ranges: &[Range]) -> &'static str {
+    let mut counts: Vec<(&str, u32)> = Vec::new();
+
+    for &(script, lower, upper) in SCRIPTS {
+        let count = ranges
+            .iter()
+            .map(|r| (r.1.min(upper) + 1).saturating_sub(r.0.max(lower)))
+            .sum::<u32>();
+
+        match counts.iter_mut().find(|(s, _)| *s == script) {
+            Some((_, total)) => *total += count,
+            None => counts.push((script, count)),
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+        .map_or("Zzzz", |(script, _)| script)
+}
+
+impl<'l> Deserialize<'l> for Range {
+    fn deserialize<T>(deserializer: T) -> Result<Self, T::Error>
+    where
+        T: Deserializer<'l>,
+    {
+        deserializer.deserialize_any(RangeVisitor)
+    }
+}
+
+/// Deserializes a range from a `lower..upper`, `lower..=upper` or
+/// `lower...upper` string, a single codepoint, or a `[lower, upper]`
+/// array. As in Ruby, which the speakeasy data comes from, the
+/// `lower...upper` form leaves out the upper codepoint and the other
+/// forms include it.
+struct RangeVisitor;
+
+impl<'l> Visitor<'l> for RangeVisitor {
+    type Value = Range;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a codepoint, a `lower..upper` string or a `[lower, upper]` array")
+    }
+
+    fn visit_str<E: Error>(self, s: &str) -> Result<Range, E> {
+        let error = |e: &dyn fmt::Display| E::custom(format!("invalid range {s:?}: {e}"));
+        let codepoint = |x: &str| {
+            x.trim()
+                .parse::<u32>()
+                .map_err(|_| error(&format_args!("{x:?} is not a codepoint")))
+        };
+
+        match s.split_once("..") {
+            Some((lower, upper)) => {
+                let (upper, exclusive) = match upper.strip_prefix('.') {
+                    Some(upper) => (upper, true),
+                    None => (upper.strip_prefix('=').unwrap_or(upper), false),
+                };
+                if upper.contains("..") {
+                    return Err(error(&"expected exactly two codepoints"));
+                }
+
+                let (lower, upper) = (codepoint(lower)?, codepoint(upper)?);
+                if !exclusive {
+                    Ok(Range(lower, upper))
+                } else if lower == upper {
+                    Err(error(&"empty range"))
+                } else {
+                    Ok(Range(lower, upper.saturating_sub(1)))
+                }
+            }
+            None => codepoint(s).map(|i| Range(i, i)),
+        }
+    }
+
+    fn visit_u64<E: Error>(self, i: u64) -> Result<Range, E> {
+        u32::try_from(i).map(|i| Range(i, i)).map_err(E::custom)
+    }
+
+    fn visit_i64<E: Error>(self, i: i64) -> Result<Range, E> {
+        u32::try_from(i).map(|i| Range(i, i)).map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'l>>(self, mut seq: A) -> Result<Range, A::Error> {
+        let lower = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let upper = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+
+        Ok(Range(lower, upper))
+    }
+}
+
+/// Parses a language from a file, using the file name without a
+/// `.json`, `.yaml` or `.yml` extension as the tag.
+///
+/// Files with a `.json` extension are parsed as JSON and all other
+/// files as YAML. Returns an error naming the file if it cannot be
+/// read or parsed, if its name is not a valid language tag or if one of
+/// its ranges is reversed or above the largest Unicode codepoint.
+pub fn parse_language<T: AsRef<Path>>(path: T) -> Result<Language, String> {
+    let path = path.as_ref();
+    let error = |e: &dyn fmt::Display| format!("{}: {e}", path.display());
+
+    let tag = language_tag(path)?;
+    let s = read_to_string(path).map_err(|e| error(&e))?;
+
+    let d: Language = if path.extension().is_some_and(|e| e == "json") {
+        serde_json::from_str(&s).map_err(|e| error(&e))?
+    } else {
+        // The Serde YAML parser expects YAML types to have names that are valid
+        // Rust identifiers. Sadly, that is not the case here, so we manually perform
+        // a string replace to patch up the data.
+        serde_yml::from_str(&s.replace("ruby/range", "Range")).map_err(|e| error(&e))?
+    };
+
+    let ranges = || d.codepoints.iter().chain(&d.auxiliary);
+
+    if let Some(c) = ranges().find(|c| c.0 > c.1) {
+        return Err(error(&format!("reversed range {}..{}", c.0, c.1)));
+    }
+
+    if let Some(c) = ranges().find(|c| c.1 > MAX_CODEPOINT) {
+        let value = if c.0 == c.1 {
+            format!("codepoint {} (U+{:X})", c.0, c.0)
+        } else {
+            format!("range {}..{}", c.0, c.1)
+        };
+        return Err(error(&format!("{value} is above U+{MAX_CODEPOINT:X}")));
+    }
+
+    Ok(finish(d, path, tag))
+}
+
+/// Returns the language tag of a data file, which is its file name
+/// without a `.json`, `.yaml` or `.yml` extension.
+///
+/// Returns an error naming the file if its name is not valid UTF-8 or
+/// not a valid language tag, such as for a stray `README.md` in the
+/// data directory.
+pub fn language_tag(path: &Path) -> Result<&str, String> {
+    let error = |e: &dyn fmt::Display| format!("{}: {e}", path.display());
+
+    // Speakeasy files have no extension, but other files may.
+    let tag = if path
+        .extension()
+        .is_some_and(|e| e == "json" || e == "yaml" || e == "yml")
+    {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+
+    let tag = tag
+        .and_then(OsStr::to_str)
+        .ok_or_else(|| error(&"file name is not valid UTF-8"))?;
+
+    if LanguageTag::parse(tag).is_err() {
+        return Err(error(&format!("invalid language tag {tag:?}")));
+    }
+
+    Ok(tag)
+}
+
+/// Sets the tag, script and path of a parsed language and normalizes
+/// its ranges.
+fn finish(mut d: Language, path: &Path, tag: &str) -> Language {
+    d.tag = Some(tag.to_string());
+    d.path = path.to_path_buf();
+
+    if d.native_name.is_none() {
+        d.native_name = Some(d.anglicized_name.clone());
+    }
+
+    // Sort the ranges so we can exit early when running the detection code.
+    d.codepoints.sort_by_key(|c| c.0);
+
+    // Merge overlapping and adjacent ranges so no codepoint is counted twice
+    // and the generated data stays small. `coalesce` checks that merging
+    // does not change the total.
+    d.codepoints = coalesce(d.codepoints);
+
+    // Auxiliary codepoints are normalized the same way, without the
+    // codepoints that are already main codepoints.
+    d.auxiliary.sort_by_key(|c| c.0);
+    d.auxiliary = subtract(&coalesce(d.auxiliary), &d.codepoints);
+
+    // Prefer an explicit script, then the script subtag of the language
+    // tag, and finally guess it from the codepoints.
+    if d.script.is_none() {
+        d.script = LanguageTag::parse(d.tag.as_ref().unwrap())
+            .ok()
+            .and_then(|t| t.script().map(|s| s.as_str().to_string()))
+            .or_else(|| Some(dominant_script(&d.codepoints).to_string()));
+    }
+
+    d
+}
+
+/// Merges overlapping and adjacent ranges. The ranges must be sorted by
+/// their lower bound.
+///
+/// # Panics
+///
+/// Panics if the merged ranges do not contain the same number of
+/// codepoints as the given ranges, so merging never changes a score.
+pub fn coalesce(ranges: Vec<Range>) -> Vec<Range> {
+    let mut result: Vec<Range> = Vec::with_capacity(ranges.len());
+    // The distinct codepoints of the given ranges, counted as the part
+    // of each range above the highest codepoint seen so far.
+    let mut distinct: u64 = 0;
+    let mut highest: Option<u32> = None;
+
+    for range in ranges {
+        let lower = match highest {
+            Some(highest) => range.0.max(highest.saturating_add(1)),
+            None => range.0,
+        };
+        if range.1 >= lower && highest.is_none_or(|highest| range.1 > highest) {
+            distinct += u64::from(range.1 - lower) + 1;
+            highest = Some(range.1);
+        }
+
+        match result.last_mut() {
+            Some(last) if range.0 <= last.1.saturating_add(1) => last.1 = last.1.max(range.1),
+            _ => result.push(range),
+        }
+    }
+
+    let merged: u64 = result.iter().map(|c| u64::from(c.1 - c.0) + 1).sum();
+    assert_eq!(merged, distinct, "merging the ranges changed their total");
+
+    result
+}
+
+/// Removes the codepoints of `other` from `ranges`. Both must be sorted
+/// and must not overlap.
+pub fn subtract(ranges: &[Range], other: &[Range]) -> Vec<Range> {
+    let mut result = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        // The lowest codepoint of the range not yet removed or kept.
+        let mut lower = Some(range.0);
+        for o in other.iter().filter(|o| o.1 >= range.0 && o.0 <= range.1) {
+            if let Some(l) = lower.filter(|&l| o.0 > l) {
+                result.push(Range(l, o.0 - 1));
+            }
+            lower = (o.1 < range.1).then(|| o.1 + 1);
+        }
+        if let Some(l) = lower {
+            result.push(Range(l, range.1));
+        }
+    }
+
+    result
+}
+
+/// Returns the number of codepoints in non-overlapping ranges.
+pub fn total(ranges: &[Range]) -> u32 {
+    ranges.iter().map(|c| c.1 - c.0 + 1).sum()
+}
+
+/// Interns identical range lists.
+///
+/// Returns the distinct range lists in order of first appearance, and
+/// for each of the given range lists the index of its distinct list.
+#[cfg_attr(feature = "bincode", allow(dead_code))]
+pub fn intern(ranges: &[Vec<Range>]) -> (Vec<&[Range]>, Vec<usize>) {
+    let mut sets: Vec<&[Range]> = Vec::new();
+    let mut seen: HashMap<&[Range], usize> = HashMap::new();
+
+    let indices = ranges
+        .iter()
+        .map(|ranges| {
+            *seen.entry(ranges).or_insert_with(|| {
+                sets.push(ranges);
+                sets.len() - 1
+            })
+        })
+        .collect();
+
+    (sets, indices)
+}
+
+/// Removes the languages without any codepoints, which could never
+/// be matched and would have a total of zero.
+///
+/// Returns the remaining languages and a warning naming each removed
+/// language.
+pub fn remove_empty(languages: Vec<Language>) -> (Vec<Language>, Vec<String>) {
+    let (empty, languages): (Vec<Language>, Vec<Language>) =
+        languages.into_iter().partition(|l| l.codepoints.is_empty());
+
+    let warnings = empty
+        .iter()
+        .map(|l| {
+            format!(
+                "{}: language {} has no codepoints and is skipped",
+                l.path.display(),
+                l.tag.as_ref().unwrap()
+            )
+        })
+        .collect();
+
+    (languages, warnings)
+}
+
+/// Checks that no two languages have the same tag.
+///
+/// Returns an error for every duplicated tag, naming the tag and the
+/// files it was found in, otherwise.
+pub fn check_duplicate_tags(languages: &[Language]) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    for (i, language) in languages.iter().enumerate() {
+        let paths: Vec<String> = languages[i..]
+            .iter()
+            .filter(|l| l.tag == language.tag)
+            .map(|l| l.path.display().to_string())
+            .collect();
+
+        if paths.len() > 1 && !languages[..i].iter().any(|l| l.tag == language.tag) {
+            errors.push(format!(
+                "duplicate language tag {} in {}",
+                language.tag.as_ref().unwrap(),
+                paths.join(", ")
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
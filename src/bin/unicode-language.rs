@@ -0,0 +1,207 @@
+//! Detects the languages supported by a font or covered by a text file.
+//!
+//! ```text
+//! unicode-language [--threshold <score>] [--top <count>] [--json] [--missing <tag>] <file>
+//! unicode-language --version
+//! ```
+
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use ttf_parser::Face;
+use unicode_language::{
+    coalesce, detect, detect_top_n, face_codepoints, language_by_tag, missing_codepoints,
+    write_report, Codepoint, Range, DATA_VERSION,
+};
+
+const USAGE: &str =
+    "usage: unicode-language [--threshold <score>] [--top <count>] [--json] [--missing <tag>] <file>";
+
+/// Command line options.
+#[derive(Debug, PartialEq)]
+struct Options {
+    path: String,
+    threshold: f64,
+    top: Option<usize>,
+    json: bool,
+    missing: Option<String>,
+}
+
+/// Parses the command line arguments, without the program name.
+fn parse_args<T>(args: T) -> Result<Options, String>
+where
+    T: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut path = None;
+    let mut options = Options {
+        path: String::new(),
+        threshold: 0.5,
+        top: None,
+        json: false,
+        missing: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} requires a value"));
+
+        match arg.as_str() {
+            "--threshold" => {
+                let threshold = value("--threshold")?;
+                options.threshold = threshold
+                    .parse()
+                    .map_err(|_| format!("invalid threshold {threshold:?}"))?;
+            }
+            "--top" => {
+                let top = value("--top")?;
+                options.top = Some(top.parse().map_err(|_| format!("invalid count {top:?}"))?);
+            }
+            "--json" => options.json = true,
+            "--missing" => options.missing = Some(value("--missing")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+
+    options.path = path.ok_or("missing file")?;
+
+    Ok(options)
+}
+
+/// Returns the codepoints of a font file, or of a UTF-8 text file if
+/// the data is not a font, as ranges.
+fn ranges(data: &[u8]) -> Result<Vec<Range<Codepoint>>, String> {
+    let codepoints = match Face::parse(data, 0) {
+        Ok(face) => face_codepoints(&face),
+        Err(_) => std::str::from_utf8(data)
+            .map_err(|_| "file is neither a font nor UTF-8 text")?
+            .chars()
+            .map(Codepoint::from)
+            .collect(),
+    };
+
+    Ok(coalesce(codepoints))
+}
+
+/// Formats a range of codepoints as `U+0041..U+005A`.
+fn format_range([lower, upper]: Range<Codepoint>) -> String {
+    if lower == upper {
+        format!("U+{lower:04X}")
+    } else {
+        format!("U+{lower:04X}..U+{upper:04X}")
+    }
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let data = fs::read(&options.path).map_err(|e| format!("{}: {e}", options.path))?;
+    let ranges = ranges(&data)?;
+
+    if let Some(tag) = options.missing {
+        let missing =
+            missing_codepoints(&tag, ranges).ok_or_else(|| format!("unknown language {tag:?}"))?;
+
+        if options.json {
+            println!("{}", serde_json::to_string(&missing).unwrap());
+        } else {
+            let language = language_by_tag(&tag).unwrap();
+            let count: u32 = missing.iter().map(|[lower, upper]| upper - lower + 1).sum();
+            println!("{} ({}): {} missing", language.name, tag, count);
+            for range in missing {
+                println!("{}", format_range(range));
+            }
+        }
+
+        return Ok(());
+    }
+
+    let matches = match options.top {
+        Some(top) => detect_top_n(ranges, options.threshold, top),
+        None => detect(ranges, options.threshold),
+    };
+
+    if options.json {
+        println!("{}", serde_json::to_string(&matches).unwrap());
+    } else {
+        let mut table = String::new();
+        write_report(&mut table, &matches).unwrap();
+        print!("{table}");
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if env::args().skip(1).any(|arg| arg == "--version") {
+        println!(
+            "unicode-language {} (data {DATA_VERSION})",
+            env!("CARGO_PKG_VERSION")
+        );
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("unicode-language: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("unicode-language: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn it_parses_arguments() {
+        assert_eq!(
+            args(&["--threshold", "0.8", "--top", "3", "--json", "font.otf"]),
+            Ok(Options {
+                path: "font.otf".to_string(),
+                threshold: 0.8,
+                top: Some(3),
+                json: true,
+                missing: None,
+            })
+        );
+        assert_eq!(
+            args(&["text.txt", "--missing", "en"]).unwrap().missing,
+            Some("en".to_string())
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_arguments() {
+        assert!(args(&[]).is_err());
+        assert!(args(&["--top"]).is_err());
+        assert!(args(&["--threshold", "high", "font.otf"]).is_err());
+        assert!(args(&["--verbose", "font.otf"]).is_err());
+        assert!(args(&["a.otf", "b.otf"]).is_err());
+    }
+
+    #[test]
+    fn it_reads_text_files() {
+        assert_eq!(ranges("abcea".as_bytes()), Ok(vec![[97, 99], [101, 101]]));
+        assert!(ranges(&[0xFF, 0xFE]).is_err());
+    }
+
+    #[test]
+    fn it_formats_ranges() {
+        assert_eq!(format_range([65, 65]), "U+0041");
+        assert_eq!(format_range([65, 90]), "U+0041..U+005A");
+    }
+}
@@ -0,0 +1,65 @@
+//! Language ranges decoded from the binary data written by the build
+//! script when the `bincode` feature is enabled.
+
+use core::iter::Copied;
+use core::ops::Deref;
+use core::slice::Iter;
+use std::sync::OnceLock;
+use std::vec::Vec;
+
+use crate::{Codepoint, Range, SUPPORTED_LANGUAGE_COUNT};
+
+static DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/ranges.bin"));
+
+static DECODED: OnceLock<Vec<Vec<Range<Codepoint>>>> = OnceLock::new();
+
+static RANGES: OnceLock<[&'static [Range<Codepoint>]; SUPPORTED_LANGUAGE_COUNT]> = OnceLock::new();
+
+/// Returns the ranges of each language, decoding them on first use.
+fn ranges() -> &'static [&'static [Range<Codepoint>]; SUPPORTED_LANGUAGE_COUNT] {
+    RANGES.get_or_init(|| {
+        let decoded = DECODED.get_or_init(|| bincode::deserialize(DATA).unwrap());
+
+        core::array::from_fn(|i| decoded[i].as_slice())
+    })
+}
+
+/// The ranges of each language.
+///
+/// This dereferences to the same array as the constant generated
+/// without the `bincode` feature. Tests use stub data instead, so the
+/// array has one entry per language of the real data.
+#[derive(Clone, Copy)]
+pub(crate) struct Ranges;
+
+impl Deref for Ranges {
+    type Target = [&'static [Range<Codepoint>]; SUPPORTED_LANGUAGE_COUNT];
+
+    fn deref(&self) -> &Self::Target {
+        ranges()
+    }
+}
+
+impl IntoIterator for Ranges {
+    type Item = &'static [Range<Codepoint>];
+    type IntoIter = Copied<Iter<'static, &'static [Range<Codepoint>]>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ranges().iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CONST_RANGES;
+
+    #[test]
+    fn it_decodes_the_same_ranges_as_the_constants() {
+        let decoded: Vec<Vec<Range<Codepoint>>> = bincode::deserialize(DATA).unwrap();
+
+        assert_eq!(decoded.len(), SUPPORTED_LANGUAGE_COUNT);
+        assert_eq!(*Ranges, CONST_RANGES);
+        assert!(Ranges.into_iter().eq(CONST_RANGES));
+    }
+}
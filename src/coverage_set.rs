@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+
+use crate::{counts, matches, normalize_ranges, Codepoint, Match, Range, LANGUAGE_COUNT};
+
+/// The codepoints of an input, prepared for repeated detection.
+///
+/// The per-language counts are computed once when the set is created,
+/// so detecting with many thresholds only filters and sorts the
+/// matches instead of counting the codepoints again. The codepoints
+/// are stored as sorted, coalesced ranges, so a set takes memory
+/// proportional to the number of ranges rather than the number of
+/// codepoints, plus one count per language. Creating a set costs about
+/// the same as a call to `detect`.
+#[derive(Clone, Debug)]
+pub struct CoverageSet {
+    ranges: Vec<Range<Codepoint>>,
+    counts: [u32; LANGUAGE_COUNT],
+}
+
+impl CoverageSet {
+    /// Creates a set from codepoint ranges, in any order.
+    pub fn new<T>(codepoints: T) -> Self
+    where
+        T: IntoIterator<Item = Range<Codepoint>>,
+    {
+        let ranges = normalize_ranges(codepoints);
+        let counts = counts(ranges.iter().copied());
+
+        CoverageSet { ranges, counts }
+    }
+
+    /// Returns the codepoints of the set as sorted, coalesced ranges.
+    pub fn ranges(&self) -> &[Range<Codepoint>] {
+        &self.ranges
+    }
+
+    /// Checks whether the set contains a codepoint.
+    pub fn contains(&self, codepoint: Codepoint) -> bool {
+        let i = self.ranges.partition_point(|[_, upper]| *upper < codepoint);
+        self.ranges
+            .get(i)
+            .is_some_and(|[lower, _]| *lower <= codepoint)
+    }
+
+    /// Detects language support for the codepoints of the set.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The minimum score a language must have to be
+    ///   returned as a match. Value must be between 0 and 1.
+    ///
+    /// Returns the same matches as `detect` for the same codepoints.
+    pub fn detect(&self, threshold: f64) -> Vec<Match> {
+        let mut matches: Vec<Match> = matches(self.counts, threshold).collect();
+        matches.sort();
+        matches
+    }
+}
+
+impl FromIterator<Range<Codepoint>> for CoverageSet {
+    fn from_iter<T: IntoIterator<Item = Range<Codepoint>>>(iter: T) -> Self {
+        CoverageSet::new(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect;
+
+    #[test]
+    fn it_returns_the_same_matches_as_detect() {
+        let set = CoverageSet::new([[8, 9], [1, 2], [3, 6]]);
+
+        for threshold in [0.0, 0.5, 1.0] {
+            assert_eq!(set.detect(threshold), detect([[1, 6], [8, 9]], threshold));
+        }
+    }
+
+    #[test]
+    fn it_contains_codepoints() {
+        let set: CoverageSet = [[4, 6], [1, 2]].into_iter().collect();
+
+        assert_eq!(set.ranges(), [[1, 2], [4, 6]]);
+        assert!(set.contains(1));
+        assert!(set.contains(6));
+        assert!(!set.contains(3));
+        assert!(!set.contains(7));
+    }
+}
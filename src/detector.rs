@@ -0,0 +1,380 @@
+#[cfg(feature = "alloc")]
+use alloc::{sync::Arc, vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::cmp;
+
+#[cfg(feature = "alloc")]
+use crate::{clamp_threshold, matches, normalize_ranges, ratio, Match};
+use crate::{intersection, Codepoint, Range, LANGUAGE_COUNT, RANGES};
+
+/// A language definition supplied at runtime.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomLanguage {
+    /// BCP 47 language tag.
+    pub tag: &'static str,
+    /// English name.
+    pub name: &'static str,
+    /// Name in native script.
+    pub native: &'static str,
+    /// ISO 15924 script code, such as `Latn` or `Cyrl`.
+    pub script: &'static str,
+    /// Codepoint ranges required by the language, in any order.
+    pub ranges: Vec<Range<Codepoint>>,
+}
+
+/// Detects language support incrementally.
+///
+/// Codepoints can be added in any order and over multiple calls, and
+/// the per-language counts are updated as they are added. A codepoint
+/// must not be added more than once, so the added ranges must not
+/// overlap.
+///
+/// Detectors are `Send` and `Sync`. Cloning a detector copies its
+/// counts but shares its custom languages, so a detector created once
+/// with `with_custom` can be cloned cheaply for every input, including
+/// from other threads.
+#[derive(Clone, Debug)]
+pub struct Detector {
+    counts: [u32; LANGUAGE_COUNT],
+    #[cfg(feature = "alloc")]
+    custom: Arc<[CustomLanguage]>,
+    #[cfg(feature = "alloc")]
+    custom_counts: Vec<u32>,
+}
+
+impl Detector {
+    /// Creates a detector without any codepoints.
+    pub fn new() -> Self {
+        Detector {
+            counts: [0; LANGUAGE_COUNT],
+            #[cfg(feature = "alloc")]
+            custom: Arc::new([]),
+            #[cfg(feature = "alloc")]
+            custom_counts: Vec::new(),
+        }
+    }
+
+    /// Creates a detector without any codepoints that also detects
+    /// the given languages in addition to the built-in ones.
+    ///
+    /// The ranges of each language are sorted and merged, so they may
+    /// be given in any order and may overlap.
+    #[cfg(feature = "alloc")]
+    pub fn with_custom(languages: Vec<CustomLanguage>) -> Self {
+        let custom: Arc<[CustomLanguage]> = languages
+            .into_iter()
+            .map(|mut language| {
+                language.ranges = normalize_ranges(language.ranges);
+                language
+            })
+            .collect();
+
+        Detector {
+            counts: [0; LANGUAGE_COUNT],
+            custom_counts: vec![0; custom.len()],
+            custom,
+        }
+    }
+
+    /// Adds a language to detect in addition to the built-in ones.
+    ///
+    /// The ranges of the language are sorted and merged, so they may
+    /// be given in any order and may overlap. The language only counts
+    /// the codepoints added after it, so languages should be added
+    /// before any codepoints. Clones made before the call do not see
+    /// the language.
+    #[cfg(feature = "alloc")]
+    pub fn add_language(&mut self, mut language: CustomLanguage) {
+        language.ranges = normalize_ranges(language.ranges);
+
+        let mut custom = self.custom.to_vec();
+        custom.push(language);
+        self.custom = custom.into();
+        self.custom_counts.push(0);
+    }
+
+    /// Detects language support given a list of Unicode codepoint
+    /// ranges, including the custom languages of the detector.
+    ///
+    /// Unlike `finish`, this leaves the detector unchanged and ignores
+    /// the codepoints added to it, so a detector with custom languages
+    /// can be reused for many inputs. Unlike `add_range`, the ranges
+    /// are merged first, so codepoints shared by overlapping ranges
+    /// are only counted once.
+    ///
+    /// # Arguments
+    ///
+    /// * `codepoints` - An iterator of codepoint ranges, in any order.
+    /// * `threshold` - The minimum score a language must have to be
+    ///   returned as a match. Value must be between 0 and 1.
+    ///
+    /// Returns a vector of language matches, sorted the same way as in
+    /// `detect`.
+    #[cfg(feature = "alloc")]
+    pub fn detect<T>(&self, codepoints: T, threshold: f64) -> Vec<Match>
+    where
+        T: IntoIterator<Item = Range<Codepoint>>,
+    {
+        let mut detector = Detector {
+            counts: [0; LANGUAGE_COUNT],
+            custom: Arc::clone(&self.custom),
+            custom_counts: vec![0; self.custom.len()],
+        };
+
+        for range in normalize_ranges(codepoints) {
+            detector.add_range(range);
+        }
+
+        detector.finish(threshold)
+    }
+
+    /// Adds a range of codepoints.
+    pub fn add_range(&mut self, range: Range<Codepoint>) {
+        for (count, ranges) in self.counts.iter_mut().zip(RANGES) {
+            *count = count.saturating_add(intersection(range, ranges));
+        }
+
+        #[cfg(feature = "alloc")]
+        for (language, count) in self.custom.iter().zip(&mut self.custom_counts) {
+            *count = count.saturating_add(intersection(range, &language.ranges));
+        }
+    }
+
+    /// Adds a single codepoint.
+    pub fn add_codepoint(&mut self, codepoint: Codepoint) {
+        self.add_range([codepoint, codepoint]);
+    }
+
+    /// Finishes detection.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The minimum score a language must have to be
+    ///   returned as a match. Value must be between 0 and 1.
+    ///
+    /// Returns a vector of language matches, sorted the same way as in
+    /// `detect`.
+    #[cfg(feature = "alloc")]
+    pub fn finish(self, threshold: f64) -> Vec<Match> {
+        let mut result: Vec<Match> = matches(self.counts, threshold).collect();
+
+        let threshold = clamp_threshold(threshold);
+
+        let custom = self.custom.iter().zip(self.custom_counts);
+
+        result.extend(custom.filter_map(|(language, count)| {
+            let total = language.ranges.iter().fold(0u32, |total, [lower, upper]| {
+                total.saturating_add(upper - lower + 1)
+            });
+            // Ranges added more than once are counted again, so the count
+            // is capped the same way as for the built-in languages.
+            let count = cmp::min(count, total);
+            let score = ratio(count, total);
+
+            (score >= threshold && count > 0).then_some(Match {
+                tag: language.tag,
+                name: language.name,
+                native: language.native,
+                script: language.script,
+                count,
+                total,
+                score,
+                speakers: None,
+            })
+        }));
+
+        result.sort();
+
+        result
+    }
+}
+
+impl Default for Detector {
+    fn default() -> Self {
+        Detector::new()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::detect;
+    use alloc::vec;
+
+    #[test]
+    fn it_returns_an_empty_array() {
+        let result = Detector::new().finish(0.0);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn it_accumulates_ranges() {
+        let mut detector = Detector::new();
+        detector.add_range([1, 1]);
+        detector.add_range([2, 3]);
+
+        let result = detector.finish(1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+    }
+
+    #[test]
+    fn it_accumulates_unsorted_codepoints() {
+        let mut detector = Detector::default();
+        detector.add_codepoint(8);
+        detector.add_codepoint(3);
+        detector.add_codepoint(1);
+
+        let result = detector.finish(0.0);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].tag, "t4");
+        assert_eq!(result[1].tag, "t1");
+        assert_eq!(result[1].count, 2);
+        assert_eq!(result[2].tag, "t3");
+    }
+
+    #[test]
+    fn it_saturates_counts() {
+        let mut detector = Detector::new();
+        detector.counts[0] = u32::MAX - 1;
+        detector.add_range([0, u32::MAX]);
+        assert_eq!(detector.counts[0], u32::MAX);
+
+        let result = detector.finish(0.0);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[0].score, 1.0);
+    }
+
+    #[test]
+    fn it_detects_custom_languages() {
+        let custom = CustomLanguage {
+            tag: "x1",
+            name: "custom1",
+            native: "ncustom1",
+            script: "Latn",
+            ranges: vec![[12, 13], [3, 4], [10, 11]],
+        };
+        let mut detector = Detector::with_custom(vec![custom]);
+        detector.add_range([1, 3]);
+        detector.add_range([10, 13]);
+
+        let result = detector.finish(0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[1].tag, "x1");
+        assert_eq!(result[1].native, "ncustom1");
+        assert_eq!(result[1].count, 5);
+        assert_eq!(result[1].score, 5.0 / 6.0);
+    }
+
+    #[test]
+    fn it_shares_custom_languages_between_clones() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Detector>();
+
+        let custom = CustomLanguage {
+            tag: "x1",
+            name: "custom1",
+            native: "ncustom1",
+            script: "Latn",
+            ranges: vec![[10, 11]],
+        };
+        let detector = Detector::with_custom(vec![custom]);
+
+        let mut a = detector.clone();
+        a.add_range([10, 11]);
+        let b = detector.clone();
+        assert!(Arc::ptr_eq(&a.custom, &b.custom));
+
+        let handle = std::thread::spawn(move || a.finish(0.0));
+        assert_eq!(handle.join().unwrap()[0].tag, "x1");
+        assert!(b.finish(0.0).is_empty());
+    }
+
+    #[test]
+    fn it_adds_languages() {
+        let mut detector = Detector::new();
+        detector.add_language(CustomLanguage {
+            tag: "x1",
+            name: "custom1",
+            native: "ncustom1",
+            script: "Latn",
+            ranges: vec![[11, 12], [10, 10]],
+        });
+        detector.add_language(CustomLanguage {
+            tag: "x2",
+            name: "custom2",
+            native: "ncustom2",
+            script: "Latn",
+            ranges: vec![[20, 21]],
+        });
+        assert_eq!(detector.custom[0].ranges, [[10, 12]]);
+
+        let result = detector.detect([[1, 3], [10, 12], [21, 21]], 0.5);
+        let tags: Vec<_> = result.iter().map(|m| (m.tag, m.count)).collect();
+        assert_eq!(tags, [("t1", 3), ("x1", 3), ("x2", 1)]);
+    }
+
+    #[test]
+    fn it_counts_overlapping_input_once() {
+        let detector = Detector::with_custom(vec![CustomLanguage {
+            tag: "x1",
+            name: "custom1",
+            native: "ncustom1",
+            script: "Latn",
+            ranges: vec![[10, 12]],
+        }]);
+
+        let result = detector.detect([[11, 12], [10, 11], [2, 3], [1, 2]], 1.0);
+        let tags: Vec<_> = result.iter().map(|m| (m.tag, m.count)).collect();
+        assert_eq!(tags, [("t1", 3), ("x1", 3)]);
+        assert!(result.iter().all(|m| m.score == 1.0));
+    }
+
+    #[test]
+    fn it_detects_without_changing_the_detector() {
+        let mut detector = Detector::with_custom(vec![CustomLanguage {
+            tag: "x1",
+            name: "custom1",
+            native: "ncustom1",
+            script: "Latn",
+            ranges: vec![[10, 11]],
+        }]);
+        detector.add_range([1, 3]);
+
+        assert_eq!(detector.detect([[10, 11]], 0.0)[0].tag, "x1");
+        assert_eq!(detector.detect([[10, 11]], 0.0).len(), 1);
+        assert_eq!(detector.finish(0.0)[0].tag, "t1");
+    }
+
+    #[test]
+    fn it_ignores_custom_languages_without_codepoints() {
+        let custom = CustomLanguage {
+            tag: "x1",
+            name: "custom1",
+            native: "ncustom1",
+            script: "Latn",
+            ranges: vec![],
+        };
+        let mut detector = Detector::with_custom(vec![custom]);
+        detector.add_range([0, 20]);
+
+        let result = detector.finish(0.0);
+        assert!(result.iter().all(|m| m.tag != "x1"));
+    }
+
+    #[test]
+    fn it_returns_the_same_matches_as_detect() {
+        let input = [[1, 1], [4, 6], [8, 9], [16, 16]];
+
+        let mut detector = Detector::new();
+        for range in input {
+            detector.add_range(range);
+        }
+
+        assert_eq!(detector.finish(0.0), detect(input, 0.0));
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Serialize;
+
+use crate::{
+    codepoint_count, detect, normalize_ranges, Codepoint, Match, Range, LANGUAGE_COUNT, METADATA,
+    RANGES, TOTALS,
+};
+
+/// A language as it is compiled into the library.
+#[derive(Serialize)]
+struct Language {
+    tag: &'static str,
+    name: &'static str,
+    native: &'static str,
+    script: &'static str,
+    total: u32,
+    ranges: &'static [Range<Codepoint>],
+}
+
+/// Exports the embedded language data as a JSON array.
+///
+/// Each language is serialized to a map with the fields `tag`,
+/// `name`, `native`, `script`, `total` and `ranges`, in the order of
+/// the generated data.
+pub fn export_json() -> String {
+    let languages = METADATA
+        .iter()
+        .zip(TOTALS)
+        .zip(RANGES)
+        .map(|((metadata, total), ranges)| Language {
+            tag: metadata.tag,
+            name: metadata.name,
+            native: metadata.native_name,
+            script: metadata.script,
+            total,
+            ranges,
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string(&languages).unwrap()
+}
+
+/// The result of a detection run, with the input summarized.
+///
+/// This serializes to a map with the fields `threshold`,
+/// `range_count`, `codepoint_count`, `language_count`, `match_count`
+/// and `matches`, in that order.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Report {
+    /// The threshold used.
+    pub threshold: f64,
+    /// Number of ranges in the input.
+    pub range_count: usize,
+    /// Number of distinct codepoints in the input, not counting
+    /// surrogates, which are never matched.
+    pub codepoint_count: u32,
+    /// Number of languages compiled into the library.
+    pub language_count: usize,
+    /// Number of matches.
+    pub match_count: usize,
+    /// The matches, sorted the same way as in `detect`.
+    pub matches: Vec<Match>,
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges and returns a report of the run.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+pub fn detect_report<T>(codepoints: T, threshold: f64) -> Report
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let codepoints: Vec<Range<Codepoint>> = codepoints.into_iter().collect();
+    let matches = detect(codepoints.iter().copied(), threshold);
+
+    Report {
+        threshold,
+        range_count: codepoints.len(),
+        codepoint_count: codepoint_count(&normalize_ranges(codepoints)),
+        language_count: LANGUAGE_COUNT,
+        match_count: matches.len(),
+        matches,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_exports_the_language_data() {
+        let json = export_json();
+        assert!(json.starts_with(
+            r#"[{"tag":"t1","name":"test1","native":"ntest1","script":"Latn","total":3,"ranges":[[1,3]]},"#
+        ));
+        assert!(json.ends_with(r#""total":1,"ranges":[[16,16]]}]"#));
+    }
+
+    #[test]
+    fn it_serializes_a_report() {
+        let report = detect_report([[1, 3], [8, 8]], 0.5);
+
+        assert_eq!(report.range_count, 2);
+        assert_eq!(report.codepoint_count, 4);
+        assert_eq!(report.match_count, 2);
+        assert_eq!(
+            serde_json::to_string(&Report {
+                matches: Vec::new(),
+                ..report
+            })
+            .unwrap(),
+            r#"{"threshold":0.5,"range_count":2,"codepoint_count":4,"language_count":5,"match_count":2,"matches":[]}"#
+        );
+    }
+
+    #[test]
+    fn it_counts_distinct_input_codepoints() {
+        let report = detect_report([[2, 3], [1, 2], [0xD7FF, 0xE000]], 1.0);
+
+        assert_eq!(report.range_count, 3);
+        assert_eq!(report.codepoint_count, 5);
+        assert_eq!(report.matches[0].tag, "t1");
+        assert_eq!(report.matches[0].count, 3);
+    }
+}
@@ -0,0 +1,244 @@
+use alloc::vec::Vec;
+#[cfg(feature = "font")]
+use core::fmt;
+use ttf_parser::Face;
+#[cfg(feature = "font")]
+use ttf_parser::FaceParsingError;
+
+use crate::{detect_codepoints, Codepoint, Match};
+
+/// Detects language support in a font face.
+///
+/// The coverage of the font is the union of all Unicode `cmap`
+/// subtables. Codepoints that are not mapped to a glyph are ignored.
+///
+/// # Arguments
+///
+/// * `face` - The font face to detect languages in.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches.
+pub fn detect_face(face: &Face, threshold: f64) -> Vec<Match> {
+    detect_codepoints(face_codepoints(face), threshold)
+}
+
+/// An error returned when font data cannot be parsed.
+#[cfg(feature = "font")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FontError(FaceParsingError);
+
+#[cfg(feature = "font")]
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid font data: {}", self.0)
+    }
+}
+
+#[cfg(all(feature = "font", feature = "std"))]
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Detects language support in a font file.
+///
+/// The data is parsed with `ttf-parser` and passed to `detect_face`.
+/// For a font collection, the first font is used.
+///
+/// # Arguments
+///
+/// * `data` - The contents of a TrueType or OpenType font file.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches, or an error if the data is
+/// not a font.
+#[cfg(feature = "font")]
+pub fn detect_font(data: &[u8], threshold: f64) -> Result<Vec<Match>, FontError> {
+    Face::parse(data, 0)
+        .map(|face| detect_face(&face, threshold))
+        .map_err(FontError)
+}
+
+/// Returns all codepoints mapped to a glyph in the font face.
+///
+/// The codepoints are collected from all Unicode `cmap` subtables, so
+/// they may be unsorted and contain duplicates.
+pub fn face_codepoints(face: &Face) -> Vec<Codepoint> {
+    let mut codepoints = Vec::new();
+
+    if let Some(cmap) = face.tables().cmap {
+        for subtable in cmap.subtables {
+            if !subtable.is_unicode() {
+                continue;
+            }
+
+            subtable.codepoints(|codepoint| {
+                if subtable.glyph_index(codepoint).is_some_and(|id| id.0 != 0) {
+                    codepoints.push(codepoint);
+                }
+            });
+        }
+    }
+
+    codepoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ttf_parser::RawFaceTables;
+
+    /// Builds a format 12 `cmap` subtable from a list of
+    /// `(start, end, start_glyph)` groups.
+    fn subtable(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(12u16.to_be_bytes());
+        data.extend(0u16.to_be_bytes());
+        data.extend((16 + 12 * groups.len() as u32).to_be_bytes());
+        data.extend(0u32.to_be_bytes());
+        data.extend((groups.len() as u32).to_be_bytes());
+        for (start, end, glyph) in groups {
+            data.extend(start.to_be_bytes());
+            data.extend(end.to_be_bytes());
+            data.extend(glyph.to_be_bytes());
+        }
+        data
+    }
+
+    /// Builds a `cmap` table from a list of
+    /// `(platform, encoding, subtable)` records.
+    fn cmap(records: &[(u16, u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(0u16.to_be_bytes());
+        data.extend((records.len() as u16).to_be_bytes());
+
+        let mut offset = 4 + 8 * records.len() as u32;
+        for (platform, encoding, subtable) in records {
+            data.extend(platform.to_be_bytes());
+            data.extend(encoding.to_be_bytes());
+            data.extend(offset.to_be_bytes());
+            offset += subtable.len() as u32;
+        }
+        for (_, _, subtable) in records {
+            data.extend(subtable);
+        }
+        data
+    }
+
+    fn detect_cmap(cmap: &[u8], threshold: f64) -> Vec<Match> {
+        let mut head = [0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let hhea = [0; 36];
+        let maxp = [0, 0, 0x50, 0, 0, 16];
+
+        let face = Face::from_raw_tables(RawFaceTables {
+            head: &head,
+            hhea: &hhea,
+            maxp: &maxp,
+            cmap: Some(cmap),
+            ..Default::default()
+        })
+        .unwrap();
+
+        detect_face(&face, threshold)
+    }
+
+    /// Builds a font file from a list of `(tag, table)` records.
+    #[cfg(feature = "font")]
+    fn font(tables: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(0x00010000u32.to_be_bytes());
+        data.extend((tables.len() as u16).to_be_bytes());
+        data.extend([0; 6]);
+
+        let mut offset = 12 + 16 * tables.len() as u32;
+        for (tag, table) in tables {
+            data.extend(*tag);
+            data.extend(0u32.to_be_bytes());
+            data.extend(offset.to_be_bytes());
+            data.extend((table.len() as u32).to_be_bytes());
+            offset += table.len() as u32;
+        }
+        for (_, table) in tables {
+            data.extend(*table);
+        }
+        data
+    }
+
+    #[cfg(feature = "font")]
+    #[test]
+    fn it_detects_a_font_file() {
+        let mut head = [0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let cmap = cmap(&[(3, 10, subtable(&[(1, 3, 1)]))]);
+        let data = font(&[
+            (b"cmap", &cmap),
+            (b"head", &head),
+            (b"hhea", &[0; 36]),
+            (b"maxp", &[0, 0, 0x50, 0, 0, 16]),
+        ]);
+
+        let result = detect_font(&data, 1.0).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+    }
+
+    #[cfg(feature = "font")]
+    #[test]
+    fn it_rejects_invalid_font_files() {
+        let error = detect_font(b"not a font", 0.0).unwrap_err();
+        assert_eq!(error, FontError(FaceParsingError::UnknownMagic));
+        assert_eq!(
+            alloc::string::ToString::to_string(&error),
+            "invalid font data: unknown magic"
+        );
+    }
+
+    #[test]
+    fn it_detects_a_face() {
+        let result = detect_cmap(&cmap(&[(3, 10, subtable(&[(1, 3, 1)]))]), 1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+    }
+
+    #[test]
+    fn it_ignores_unmapped_codepoints() {
+        let result = detect_cmap(&cmap(&[(3, 10, subtable(&[(1, 3, 0)]))]), 0.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 2);
+    }
+
+    #[test]
+    fn it_unions_multiple_subtables() {
+        let result = detect_cmap(
+            &cmap(&[
+                (0, 4, subtable(&[(1, 2, 1)])),
+                (3, 10, subtable(&[(2, 4, 2)])),
+            ]),
+            0.0,
+        );
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[1].tag, "t2");
+        assert_eq!(result[1].count, 1);
+    }
+
+    #[test]
+    fn it_ignores_non_unicode_subtables() {
+        let result = detect_cmap(&cmap(&[(1, 0, subtable(&[(1, 3, 1)]))]), 0.0);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn it_returns_an_empty_array_without_a_cmap() {
+        let result = detect_cmap(&cmap(&[]), 0.0);
+        assert_eq!(result.len(), 0);
+    }
+}
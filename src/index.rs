@@ -0,0 +1,138 @@
+//! A reverse index from codepoints to the languages that require them.
+//!
+//! The index is built from `RANGES` the first time it is used and is
+//! shared by all following queries. Building it splits the codepoints
+//! of all languages into segments that are each required by the same
+//! set of languages, which takes time proportional to the number of
+//! segments times the number of languages. It stores one range and one
+//! list of language indices per segment, so its size grows with the
+//! number of distinct range boundaries in the data rather than with
+//! the number of codepoints. Looking up a codepoint is a binary search
+//! over the segments.
+
+use std::sync::OnceLock;
+use std::vec::Vec;
+
+use crate::{normalize_ranges, Codepoint, Range, METADATA, RANGES};
+
+struct Index {
+    /// Sorted, non-overlapping segments and the indices of the
+    /// languages requiring them. Segments without any languages are
+    /// omitted.
+    segments: Vec<(Range<Codepoint>, Vec<usize>)>,
+    /// The codepoints required by any language, as sorted, coalesced
+    /// ranges.
+    required: Vec<Range<Codepoint>>,
+}
+
+static INDEX: OnceLock<Index> = OnceLock::new();
+
+/// Returns the index, building it on first use.
+fn index() -> &'static Index {
+    INDEX.get_or_init(build)
+}
+
+/// Returns the indices of the languages requiring a codepoint, in the
+/// order of the generated data.
+pub(crate) fn languages_at(codepoint: Codepoint) -> &'static [usize] {
+    let segments = &index().segments;
+    let i = segments.partition_point(|([_, upper], _)| *upper < codepoint);
+
+    match segments.get(i) {
+        Some(([lower, _], languages)) if *lower <= codepoint => languages,
+        _ => &[],
+    }
+}
+
+/// Returns the codepoints required by any language, as sorted,
+/// coalesced ranges.
+pub(crate) fn required() -> &'static [Range<Codepoint>] {
+    &index().required
+}
+
+/// Returns the tags of all languages that use a codepoint.
+///
+/// The first call builds a reverse index from codepoints to
+/// languages, which is reused by following calls.
+///
+/// # Arguments
+///
+/// * `codepoint` - The codepoint to look up.
+///
+/// Returns a vector of language tags, in the order of the generated
+/// data.
+pub fn languages_using(codepoint: Codepoint) -> Vec<&'static str> {
+    languages_at(codepoint)
+        .iter()
+        .map(|&i| METADATA[i].tag)
+        .collect()
+}
+
+/// Builds the reverse index.
+fn build() -> Index {
+    let mut bounds: Vec<Codepoint> = RANGES
+        .iter()
+        .flat_map(|ranges| ranges.iter())
+        .flat_map(|&[lower, upper]| [lower, upper.saturating_add(1)])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let segments: Vec<(Range<Codepoint>, Vec<usize>)> = bounds
+        .windows(2)
+        .filter_map(|window| {
+            let [lower, next] = [window[0], window[1]];
+            let languages: Vec<usize> = RANGES
+                .iter()
+                .enumerate()
+                .filter(|(_, ranges)| contains(ranges, lower))
+                .map(|(i, _)| i)
+                .collect();
+
+            (!languages.is_empty()).then_some(([lower, next - 1], languages))
+        })
+        .collect();
+
+    let required = normalize_ranges(segments.iter().map(|(range, _)| *range));
+
+    Index { segments, required }
+}
+
+/// Checks whether sorted, non-overlapping ranges contain a codepoint.
+fn contains(ranges: &[Range<Codepoint>], codepoint: Codepoint) -> bool {
+    let i = ranges.partition_point(|[_, upper]| *upper < codepoint);
+    ranges.get(i).is_some_and(|[lower, _]| *lower <= codepoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_returns_the_languages_using_a_codepoint() {
+        assert_eq!(languages_using(1), ["t1"]);
+        assert_eq!(languages_using(6), ["t2"]);
+        assert_eq!(languages_using(8), ["t3", "t4"]);
+        assert_eq!(languages_using(9), ["t3"]);
+        assert_eq!(languages_using(16), ["t5"]);
+    }
+
+    #[test]
+    fn it_returns_no_languages_for_an_unused_codepoint() {
+        assert!(languages_using(0).is_empty());
+        assert!(languages_using(10).is_empty());
+        assert!(languages_using(Codepoint::MAX).is_empty());
+    }
+
+    #[test]
+    fn it_returns_the_language_indices_at_a_codepoint() {
+        assert_eq!(languages_at(7), [2]);
+        assert_eq!(languages_at(8), [2, 3]);
+        assert!(languages_at(12).is_empty());
+    }
+
+    #[test]
+    fn it_returns_the_required_codepoints() {
+        assert_eq!(required(), [[1, 9], [16, 16]]);
+    }
+}
@@ -1,185 +1,3608 @@
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 include!(concat!(env!("OUT_DIR"), "/data.rs"));
 
-use std::cmp;
+#[cfg(feature = "alloc")]
+use alloc::{collections::BinaryHeap, vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::cmp::Reverse;
+use core::cmp::{self, Ordering};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "alloc")]
+use core::iter;
+use core::ops::RangeInclusive;
+#[cfg(feature = "alloc")]
+use ranges::{difference, push_range};
+
+#[cfg(all(feature = "bincode", not(feature = "const-data")))]
+mod binary;
+
+#[cfg(feature = "alloc")]
+mod blocks;
+
+#[cfg(feature = "alloc")]
+mod coverage_set;
+
+mod detector;
+
+#[cfg(feature = "ttf-parser")]
+mod font;
+
+#[cfg(feature = "alloc")]
+pub mod ranges;
+
+#[cfg(all(feature = "serde", feature = "alloc"))]
+mod export;
+
+#[cfg(feature = "std")]
+mod index;
+
+#[cfg(feature = "std")]
+mod reader;
+
+mod table;
+
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "alloc")]
+pub use blocks::{block_coverage, BlockCoverage};
+
+#[cfg(feature = "alloc")]
+pub use coverage_set::CoverageSet;
+
+#[cfg(feature = "alloc")]
+pub use detector::CustomLanguage;
+pub use detector::Detector;
+
+#[cfg(feature = "ttf-parser")]
+pub use font::{detect_face, face_codepoints};
+#[cfg(feature = "font")]
+pub use font::{detect_font, FontError};
+
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub use export::{detect_report, export_json, Report};
+
+#[cfg(feature = "std")]
+pub use index::languages_using;
+
+#[cfg(feature = "std")]
+pub use reader::detect_reader;
+
+pub use table::write_report;
+
+#[cfg(feature = "wasm")]
+pub use wasm::detect_json;
+
+/// A language match.
+///
+/// With the `serde` feature enabled, matches serialize to a map with
+/// the fields `tag`, `name`, `native`, `script`, `count` and `score`,
+/// and `speakers` if the number of speakers is known.
+///
+/// Matches are ordered by score in descending order, then by count in
+/// descending order and then by tag in ascending order, so sorting a
+/// vector of matches puts the best match first. A `NaN` score is
+/// ordered after every other score, so comparing matches never panics.
+/// Two matches are equal if they have the same tag, score and count,
+/// and hashing is consistent with equality, so matches can be kept in
+/// a `HashSet` or used as `HashMap` keys.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Match {
+    /// BCP 47 language tag.
+    pub tag: &'static str,
+    /// English name.
+    pub name: &'static str,
+    /// Name in native script. Empty if the `no-native-names` feature
+    /// is enabled.
+    pub native: &'static str,
+    /// ISO 15924 script code, such as `Latn` or `Cyrl`.
+    pub script: &'static str,
+    /// Number of distinct codepoints matched, which is never more than
+    /// `total`, so the score is at most 1.
+    pub count: u32,
+    /// Number of codepoints required by the language.
+    pub total: u32,
+    /// Score (number of codepoints matched divided by the total).
+    pub score: f64,
+    /// Number of speakers, if known. Not serialized if unknown.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub speakers: Option<u64>,
+}
+
+impl Match {
+    /// Returns the score as a percentage between 0 and 100.
+    pub fn percent(&self) -> f64 {
+        self.score * 100.0
+    }
+
+    /// Returns the two-letter ISO 639-1 code of the language.
+    ///
+    /// This is the primary language subtag of `tag`, such as `sr` for
+    /// `sr-Latn`. Returns `None` if the primary subtag is not a
+    /// two-letter code, as for languages only coded in ISO 639-2 or
+    /// ISO 639-3, such as `haw`.
+    pub fn iso_639_1(&self) -> Option<&'static str> {
+        let primary = self.tag.split('-').next()?;
+
+        (primary.len() == 2 && primary.bytes().all(|b| b.is_ascii_alphabetic())).then_some(primary)
+    }
+}
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}) — {:.1}%", self.name, self.tag, self.percent())
+    }
+}
+
+impl Ord for Match {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.score.is_nan(), other.score.is_nan()) {
+            (false, false) => other.score.total_cmp(&self.score),
+            (a, b) => a.cmp(&b),
+        }
+        .then_with(|| other.count.cmp(&self.count))
+        .then_with(|| self.tag.cmp(other.tag))
+    }
+}
+
+impl PartialOrd for Match {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Match {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Match {}
+
+impl Hash for Match {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tag.hash(state);
+        self.count.hash(state);
+        // All `NaN` scores are equal to each other, so they must hash
+        // the same regardless of their bits.
+        let score = if self.score.is_nan() {
+            f64::NAN
+        } else {
+            self.score
+        };
+        score.to_bits().hash(state);
+    }
+}
+
+/// The order in which `detect_sorted` returns matches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SortBy {
+    /// By score in descending order, the same as `detect`.
+    #[default]
+    Score,
+    /// By matched codepoint count in descending order.
+    Count,
+    /// By English name in ascending order, byte by byte or using
+    /// Unicode collation with the `feruca` feature.
+    Name,
+    /// By native name in ascending order, byte by byte or using
+    /// Unicode collation with the `feruca` feature.
+    NativeName,
+    /// By number of speakers in descending order, with the languages
+    /// whose number of speakers is unknown last.
+    Speakers,
+}
+
+impl SortBy {
+    /// Compares two matches, comparing names with `collation`. Ties
+    /// fall back to the order used by `detect`.
+    #[cfg(feature = "alloc")]
+    fn compare(self, a: &Match, b: &Match, collation: &mut Collation) -> Ordering {
+        let ordering = match self {
+            SortBy::Score => Ordering::Equal,
+            SortBy::Count => b.count.cmp(&a.count),
+            SortBy::Name => collation.compare(a.name, b.name),
+            SortBy::NativeName => collation.compare(a.native, b.native),
+            SortBy::Speakers => b.speakers.cmp(&a.speakers),
+        };
+
+        ordering.then_with(|| a.cmp(b))
+    }
+}
+
+/// The comparison of names when sorting by name.
+///
+/// With the `feruca` feature enabled, names are compared using the
+/// Unicode Collation Algorithm with the root locale, so accented and
+/// non-Latin names sort next to their base letters. Otherwise they are
+/// compared byte by byte.
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+struct Collation {
+    #[cfg(feature = "feruca")]
+    collator: feruca::Collator,
+}
+
+#[cfg(feature = "alloc")]
+impl Collation {
+    /// Compares two names.
+    fn compare(&mut self, a: &str, b: &str) -> Ordering {
+        #[cfg(feature = "feruca")]
+        return self.collator.collate(a, b);
+
+        #[cfg(not(feature = "feruca"))]
+        a.cmp(b)
+    }
+}
+
+/// How `detect_with_mode` scores a language.
+///
+/// The codepoint modes count the same matched codepoints and only
+/// differ in what the count is divided by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ScoreMode {
+    /// The number of matched codepoints divided by the number of
+    /// codepoints required by the language, the same as `detect`. This
+    /// answers how complete the language is in the input.
+    #[default]
+    Completeness,
+    /// The number of matched codepoints divided by the number of
+    /// codepoints in the input. This answers how much of the input is
+    /// explained by the language, which is useful for guessing the
+    /// language of a text.
+    InputCoverage,
+    /// The number of matched codepoints divided by the number of
+    /// codepoints in either the input or the language, the Jaccard
+    /// index of the two sets. This penalizes inputs with a lot of
+    /// coverage unrelated to the language.
+    Jaccard,
+    /// The number of ranges of the language that share at least one
+    /// codepoint with the input divided by the number of its ranges.
+    /// This tells a font touching every range of a language partially
+    /// apart from one covering half of the ranges fully.
+    RangeCoverage,
+    /// The Completeness score with the language's auxiliary codepoints,
+    /// such as letters only used in loanwords, counted as well. All
+    /// auxiliary codepoints together weigh less than one main
+    /// codepoint, so a font missing only auxiliary codepoints always
+    /// scores higher than one missing a main codepoint.
+    MainWeighted,
+}
+
+impl ScoreMode {
+    /// Scores the language at `index` with `count` of its `total`
+    /// codepoints matched by the sorted, coalesced ranges of an input of
+    /// `input_total` codepoints.
+    #[cfg(feature = "alloc")]
+    fn score(
+        self,
+        index: usize,
+        (count, total): (u32, u32),
+        input: &[Range<Codepoint>],
+        input_total: u32,
+    ) -> f64 {
+        match self {
+            ScoreMode::Completeness => ratio(count, total),
+            ScoreMode::InputCoverage => count as f64 / input_total as f64,
+            ScoreMode::Jaccard => count as f64 / (input_total as f64 + total as f64 - count as f64),
+            ScoreMode::RangeCoverage => {
+                let ranges = RANGES[index];
+                let touched = ranges
+                    .iter()
+                    .filter(|&&range| intersection(range, input) > 0)
+                    .count();
+                touched as f64 / ranges.len() as f64
+            }
+            ScoreMode::MainWeighted => {
+                weighted_score(count, total, aux_count(index, input), AUX_TOTALS[index])
+            }
+        }
+    }
+}
+
+/// An error returned when the input to detection is invalid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DetectError {
+    /// A range has a lower bound greater than its upper bound.
+    ReversedRange { lower: Codepoint, upper: Codepoint },
+    /// A range starts before the range preceding it.
+    UnsortedInput {
+        previous: Range<Codepoint>,
+        range: Range<Codepoint>,
+    },
+    /// A range overlaps the range preceding it.
+    OverlappingRanges {
+        previous: Range<Codepoint>,
+        range: Range<Codepoint>,
+    },
+    /// The threshold is not a number between 0 and 1.
+    ThresholdOutOfBounds(f64),
+}
+
+impl fmt::Display for DetectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DetectError::ReversedRange { lower, upper } => {
+                write!(f, "range [{lower}, {upper}] is reversed")
+            }
+            DetectError::UnsortedInput { previous, range } => {
+                write!(f, "range {range:?} is not sorted after range {previous:?}")
+            }
+            DetectError::OverlappingRanges { previous, range } => {
+                write!(f, "range {range:?} overlaps range {previous:?}")
+            }
+            DetectError::ThresholdOutOfBounds(threshold) => {
+                write!(f, "threshold {threshold} is not between 0 and 1")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DetectError {}
+
+/// Information about a known language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LanguageInfo {
+    /// BCP 47 language tag.
+    pub tag: &'static str,
+    /// English name.
+    pub name: &'static str,
+    /// Name in native script. Empty if the `no-native-names` feature
+    /// is enabled.
+    pub native: &'static str,
+    /// ISO 15924 script code, such as `Latn` or `Cyrl`.
+    pub script: &'static str,
+    /// Number of codepoints required by the language.
+    pub total: u32,
+}
+
+/// A script match.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScriptMatch {
+    /// ISO 15924 script code, such as `Latn` or `Cyrl`.
+    pub script: &'static str,
+    /// Number of codepoints matched.
+    pub count: u32,
+    /// Number of distinct codepoints required by the languages
+    /// written in the script.
+    pub total: u32,
+    /// Score (number of codepoints matched divided by the total).
+    pub score: f64,
+}
+
+/// Coverage of a single language.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Coverage {
+    /// Number of codepoints matched.
+    pub matched_count: u32,
+    /// Number of codepoints missing.
+    pub missing_count: u32,
+    /// Number of codepoints required by the language.
+    pub total: u32,
+    /// Score (number of codepoints matched divided by the total).
+    pub score: f64,
+    /// Missing codepoints as sorted, coalesced ranges.
+    pub missing_ranges: Vec<Range<Codepoint>>,
+}
+
+#[cfg(feature = "alloc")]
+impl Coverage {
+    /// How far below 1 the score of a complete language may be, to
+    /// allow for rounding. A language missing a single codepoint out
+    /// of all Unicode codepoints still scores well below this.
+    pub const TOLERANCE: f64 = 1e-9;
+
+    /// Checks whether the language is covered completely, that is
+    /// whether its score is 1 within `Coverage::TOLERANCE`.
+    pub fn is_complete(&self) -> bool {
+        self.score >= 1.0 - Coverage::TOLERANCE
+    }
+
+    /// Returns the score as a percentage between 0 and 100.
+    pub fn percent_complete(&self) -> f64 {
+        self.score * 100.0
+    }
+}
+
+/// A language match with the matched codepoints.
+///
+/// With the `serde` feature enabled, this serializes to the same map
+/// as `Match` with an additional `matched_ranges` field.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DetailedMatch {
+    /// The match.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub inner: Match,
+    /// Codepoints of the input required by the language, as sorted,
+    /// coalesced ranges.
+    pub matched_ranges: Vec<Range<Codepoint>>,
+}
+
+#[cfg(feature = "alloc")]
+impl DetailedMatch {
+    /// Returns the matched codepoints one by one, in ascending order.
+    ///
+    /// The codepoints are produced lazily from `matched_ranges`, so
+    /// large ranges do not allocate.
+    pub fn matched_codepoints(&self) -> impl Iterator<Item = Codepoint> + '_ {
+        self.matched_ranges
+            .iter()
+            .flat_map(|&[lower, upper]| lower..=upper)
+    }
+}
+
+/// A language match scored separately for the main and auxiliary
+/// codepoints of the language, as returned by `detect_exemplars`.
+///
+/// With the `serde` feature enabled, this serializes to the same map
+/// as `Match` with additional `main_score`, `aux_count`, `aux_total`
+/// and `aux_score` fields.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExemplarMatch {
+    /// The match, scored as in `ScoreMode::MainWeighted`.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub inner: Match,
+    /// Number of main codepoints matched divided by their number, the
+    /// score of `detect`.
+    pub main_score: f64,
+    /// Number of distinct auxiliary codepoints matched.
+    pub aux_count: u32,
+    /// Number of auxiliary codepoints of the language.
+    pub aux_total: u32,
+    /// Number of auxiliary codepoints matched divided by their number.
+    /// `None` if the language has no auxiliary codepoints.
+    pub aux_score: Option<f64>,
+}
+
+/// A language that narrowly misses the threshold, as returned by
+/// `near_misses`.
+///
+/// With the `serde` feature enabled, this serializes to the same map
+/// as `Match` with an additional `missing_count` field.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NearMiss {
+    /// The match.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub inner: Match,
+    /// Number of codepoints required by the language that are missing.
+    pub missing_count: u32,
+}
+
+/// Differences in language support between two fonts.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CoverageDiff {
+    /// Matches for the languages supported by the first font only.
+    pub only_a: Vec<Match>,
+    /// Matches for the languages supported by the second font only.
+    pub only_b: Vec<Match>,
+    /// Matches in the first font for the languages supported by both.
+    pub both: Vec<Match>,
+}
+
+/// A small set of languages that explains the codepoints of a font.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Explanation {
+    /// Matches for the chosen languages, in the order they were chosen.
+    pub matches: Vec<Match>,
+    /// Codepoints not required by any of the chosen languages, as
+    /// sorted, coalesced ranges.
+    pub unexplained: Vec<Range<Codepoint>>,
+}
+
+/// A requested language that is not supported, as returned by
+/// `unsupported`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Unsupported<'a> {
+    /// The tag is not the tag of a known language.
+    Unknown(&'a str),
+    /// The language is known, but its score is below the threshold.
+    BelowThreshold(Match),
+}
+
+/// Returns all known languages, in no particular order.
+pub fn languages() -> impl Iterator<Item = LanguageInfo> {
+    METADATA
+        .iter()
+        .zip(TOTALS)
+        .map(|(metadata, total)| LanguageInfo {
+            tag: metadata.tag,
+            name: metadata.name,
+            native: metadata.native_name,
+            script: metadata.script,
+            total,
+        })
+}
+
+/// The name of a language matched by `find_languages`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NameField {
+    /// The English name.
+    Name,
+    /// The name in native script.
+    Native,
+}
+
+/// Finds the languages whose English or native name contains a query.
+///
+/// Matching is ASCII-case-insensitive, so `"deutsch"` matches `Deutsch`
+/// but `"ΕΛΛΗΝΙΚΆ"` does not match `Ελληνικά`. With the
+/// `unicode-normalization` feature enabled, the query and the names are
+/// normalized to NFC and compared using Unicode lowercase mappings
+/// instead, so any case and both composed and decomposed accents match.
+///
+/// # Arguments
+///
+/// * `query` - The text to search for. An empty query matches every
+///   language.
+///
+/// Returns the matching languages together with the name that matched,
+/// preferring the English name when both do, in the order of
+/// `languages`.
+#[cfg(feature = "alloc")]
+pub fn find_languages(query: &str) -> Vec<(LanguageInfo, NameField)> {
+    let query = fold_case(query);
+
+    languages()
+        .filter_map(|language| {
+            if contains_folded(language.name, &query) {
+                Some((language, NameField::Name))
+            } else if contains_folded(language.native, &query) {
+                Some((language, NameField::Native))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the characters of a string with their case folded for
+/// `find_languages`.
+#[cfg(feature = "alloc")]
+fn fold_case(s: &str) -> Vec<char> {
+    #[cfg(feature = "unicode-normalization")]
+    return unicode_normalization::UnicodeNormalization::nfc(s)
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    #[cfg(not(feature = "unicode-normalization"))]
+    s.chars().map(|c| c.to_ascii_lowercase()).collect()
+}
+
+/// Checks whether a name contains a query folded with `fold_case`.
+#[cfg(feature = "alloc")]
+fn contains_folded(name: &str, query: &[char]) -> bool {
+    query.is_empty() || fold_case(name).windows(query.len()).any(|w| w == query)
+}
+
+/// Returns information about the language with the given BCP 47 tag.
+///
+/// This performs a linear scan over all known languages, so callers
+/// doing many lookups may want to cache the result.
+///
+/// Returns `None` if the language is unknown.
+pub fn language_by_tag(tag: &str) -> Option<LanguageInfo> {
+    languages().find(|language| language.tag == tag)
+}
+
+/// Returns the number of codepoints required by the language with the
+/// given BCP 47 tag.
+///
+/// This is the total a match's `count` is scored against, so the
+/// number of missing codepoints is the difference between the two.
+///
+/// Returns `None` if the language is unknown.
+pub fn required_count(tag: &str) -> Option<u32> {
+    position(tag).map(|i| TOTALS[i])
+}
+
+/// Returns the codepoint ranges required by the language with the
+/// given BCP 47 tag, as sorted, non-overlapping ranges.
+///
+/// Returns `None` if the language is unknown.
+pub fn ranges_for(tag: &str) -> Option<&'static [Range<Codepoint>]> {
+    position(tag).map(|i| RANGES[i])
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges.
+///
+/// Surrogate codepoints (U+D800 to U+DFFF) are not Unicode scalar
+/// values and are never counted. A range with a lower bound greater
+/// than its upper bound contains no codepoints and is ignored, and
+/// codepoints shared by overlapping ranges are only counted once.
+/// A threshold below 0 or `NaN` is treated as 0 and a threshold above
+/// 1 is treated as 1. Use `try_detect` to reject such input
+/// instead. Languages without any matched codepoints are never
+/// returned, even with a threshold of 0.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches, sorted by score in descending
+/// order. Matches with equal scores are sorted by count in descending
+/// order and then by tag in ascending order, so the result is
+/// deterministic.
+#[cfg(feature = "alloc")]
+pub fn detect<T>(codepoints: T, threshold: f64) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut matches = Vec::new();
+    detect_into(codepoints, threshold, &mut matches);
+    matches
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges and a threshold in percent.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold_pct` - The minimum score a language must have to be
+///   returned as a match, in percent. Value must be between 0 and 100.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`, or `DetectError::ThresholdOutOfBounds` with the threshold
+/// as a fraction if it is above 100.
+#[cfg(feature = "alloc")]
+pub fn detect_pct<T>(codepoints: T, threshold_pct: u8) -> Result<Vec<Match>, DetectError>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let threshold = f64::from(threshold_pct) / 100.0;
+
+    if threshold_pct > 100 {
+        return Err(DetectError::ThresholdOutOfBounds(threshold));
+    }
+
+    Ok(detect(codepoints, threshold))
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, leaving out some languages.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `exclude` - The BCP 47 language tags of the languages to leave
+///   out. Unknown tags are ignored.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_excluding<T>(codepoints: T, threshold: f64, exclude: &[&str]) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut matches = detect(codepoints, threshold);
+    matches.retain(|m| !exclude.contains(&m.tag));
+    matches
+}
+
+/// Detects support for a set of languages in a font given a list of
+/// Unicode codepoint ranges.
+///
+/// Only the given languages are counted and scored, which is faster
+/// than detecting all languages and filtering the matches.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `include` - The BCP 47 language tags of the languages to detect.
+///   Unknown tags are ignored, and a tag given more than once is
+///   only returned once.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_among<T>(codepoints: T, threshold: f64, include: &[&str]) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut indices: Vec<usize> = include.iter().filter_map(|tag| position(tag)).collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    let mut counts = vec![0u32; indices.len()];
+
+    for input in distinct(codepoints) {
+        for (count, &index) in counts.iter_mut().zip(&indices) {
+            *count = count.saturating_add(intersection(input, RANGES[index]));
+        }
+    }
+
+    let threshold = clamp_threshold(threshold);
+    let mut matches: Vec<Match> = indices
+        .into_iter()
+        .zip(counts)
+        .map(|(index, count)| language_match(index, count))
+        .filter(|m| m.count > 0 && m.score >= threshold)
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, writing the matches into a buffer.
+///
+/// The buffer is cleared on entry and then filled with the same
+/// matches as `detect` returns, so reusing one buffer across many
+/// calls avoids allocating a new vector for each.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `out` - The buffer to write the matches into.
+#[cfg(feature = "alloc")]
+pub fn detect_into<T>(codepoints: T, threshold: f64, out: &mut Vec<Match>)
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    out.clear();
+    out.extend(matches(counts(codepoints), threshold));
+    out.sort();
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, additionally requiring a minimum number of
+/// matched codepoints.
+///
+/// Scores are relative to the size of each language, so a small
+/// alphabet can reach a high score with only a few codepoints. The
+/// `min_count` filter excludes such matches.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `min_count` - The minimum number of codepoints a language must
+///   have matched to be returned as a match.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_with<T>(codepoints: T, threshold: f64, min_count: u32) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut matches = matches(counts(codepoints), threshold)
+        .filter(|m| m.count >= min_count)
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges and returns the matched codepoints of each match.
+///
+/// This allocates a list of ranges for every match in addition to the
+/// matches themselves, so prefer `detect` if only the counts are
+/// needed.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of detailed language matches, sorted the same way
+/// as in `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_detailed<T>(codepoints: T, threshold: f64) -> Vec<DetailedMatch>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let codepoints = normalize_ranges(codepoints);
+
+    let mut matches: Vec<DetailedMatch> = matches(counts(codepoints.iter().copied()), threshold)
+        .map(|m| DetailedMatch {
+            matched_ranges: ranges::intersect(&codepoints, RANGES[position(m.tag).unwrap()]),
+            inner: m,
+        })
+        .collect();
+    matches.sort_by(|a, b| a.inner.cmp(&b.inner));
+    matches
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, scoring the main and auxiliary codepoints of each
+/// language separately.
+///
+/// Auxiliary codepoints are letters a language uses only rarely, such
+/// as in loanwords. Matches are scored and sorted as in
+/// `ScoreMode::MainWeighted`, so a font missing only auxiliary
+/// codepoints ranks above one missing a main codepoint.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum weighted score a language must have to
+///   be returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches with their main and auxiliary
+/// scores, sorted the same way as in `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_exemplars<T>(codepoints: T, threshold: f64) -> Vec<ExemplarMatch>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let codepoints = normalize_ranges(codepoints);
+    let threshold = clamp_threshold(threshold);
+
+    let mut matches: Vec<ExemplarMatch> = scores(counts(codepoints.iter().copied()))
+        .enumerate()
+        .filter(|(_, m)| m.count > 0)
+        .map(|(index, mut m)| {
+            let (aux_count, aux_total) = (aux_count(index, &codepoints), AUX_TOTALS[index]);
+            let main_score = m.score;
+            m.score = weighted_score(m.count, m.total, aux_count, aux_total);
+
+            ExemplarMatch {
+                inner: m,
+                main_score,
+                aux_count,
+                aux_total,
+                aux_score: (aux_total > 0).then(|| ratio(aux_count, aux_total)),
+            }
+        })
+        .filter(|m| m.inner.score >= threshold)
+        .collect();
+    matches.sort_by(|a, b| a.inner.cmp(&b.inner));
+    matches
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, scoring each language in the given mode.
+///
+/// With `ScoreMode::Completeness` this returns the same matches as
+/// `detect`. With `ScoreMode::InputCoverage` the score is the share
+/// of the input's codepoints that belong to the language instead, so
+/// a large input scores low even if it covers a language completely.
+/// With `ScoreMode::RangeCoverage` the score is the share of the
+/// language's ranges that the input touches at all.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `mode` - How to score each language.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_with_mode<T>(codepoints: T, threshold: f64, mode: ScoreMode) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let codepoints = normalize_ranges(codepoints);
+    let input_total = codepoint_count(&codepoints);
+    let threshold = clamp_threshold(threshold);
+
+    let mut matches: Vec<Match> = scores(counts(codepoints.iter().copied()))
+        .enumerate()
+        .map(|(index, mut m)| {
+            m.score = mode.score(index, (m.count, m.total), &codepoints, input_total);
+            m
+        })
+        .filter(|m| m.score >= threshold && m.count > 0)
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges and sorts the matches in the given order.
+///
+/// Names are sorted by comparing their bytes, so the order is not
+/// locale-aware. With the `feruca` feature enabled, they are sorted
+/// using Unicode collation instead.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `sort` - The order in which to return the matches.
+///
+/// Returns a vector of language matches.
+#[cfg(feature = "alloc")]
+pub fn detect_sorted<T>(codepoints: T, threshold: f64, sort: SortBy) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut matches = matches(counts(codepoints), threshold).collect::<Vec<_>>();
+    let mut collation = Collation::default();
+    matches.sort_by(|a, b| sort.compare(a, b, &mut collation));
+    matches
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges and returns the matches lazily.
+///
+/// The codepoints are counted eagerly when this function is called,
+/// but filtering and sorting the matches is deferred until the first
+/// match is requested. Each following match is then produced in
+/// logarithmic time, so taking only the first few matches is cheaper
+/// than sorting all of them.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns an iterator of language matches, sorted the same way as in
+/// `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_iter<T>(codepoints: T, threshold: f64) -> impl Iterator<Item = Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let counts = counts(codepoints);
+    let mut heap: Option<BinaryHeap<Reverse<Match>>> = None;
+
+    iter::from_fn(move || {
+        heap.get_or_insert_with(|| matches(counts, threshold).map(Reverse).collect())
+            .pop()
+            .map(|Reverse(m)| m)
+    })
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, validating the input first.
+///
+/// Unlike `detect`, this checks that every range has a lower bound
+/// less than or equal to its upper bound, that the ranges are sorted
+/// in ascending order and do not overlap, and that the threshold is
+/// between 0 and 1.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match.
+///
+/// Returns a vector of language matches, or the first problem found
+/// with the input.
+#[cfg(feature = "alloc")]
+pub fn detect_checked<T>(codepoints: T, threshold: f64) -> Result<Vec<Match>, DetectError>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(DetectError::ThresholdOutOfBounds(threshold));
+    }
+
+    let mut ranges: Vec<Range<Codepoint>> = Vec::new();
+
+    for range @ [lower, upper] in codepoints {
+        if lower > upper {
+            return Err(DetectError::ReversedRange { lower, upper });
+        }
+
+        if let Some(&previous @ [previous_lower, previous_upper]) = ranges.last() {
+            if lower < previous_lower {
+                return Err(DetectError::UnsortedInput { previous, range });
+            }
+
+            if lower <= previous_upper {
+                return Err(DetectError::OverlappingRanges { previous, range });
+            }
+        }
+
+        ranges.push(range);
+    }
+
+    Ok(detect(ranges, threshold))
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, returning an error for malformed input instead of
+/// normalizing it as `detect` does.
+///
+/// This is the same as `detect_checked`.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, sorted in
+///   ascending order and not overlapping.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches, or the first problem found
+/// with the input.
+#[cfg(feature = "alloc")]
+pub fn try_detect<T>(codepoints: T, threshold: f64) -> Result<Vec<Match>, DetectError>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    detect_checked(codepoints, threshold)
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges in any order.
+///
+/// The ranges are sorted and overlapping or adjacent ranges are
+/// merged before counting, so each codepoint is counted once.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges. The ranges may
+///   be unsorted and may overlap.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches.
+#[cfg(feature = "alloc")]
+pub fn detect_normalized<T>(codepoints: T, threshold: f64) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    detect(normalize_ranges(codepoints), threshold)
+}
+
+/// Detects the language with the highest score given a list of
+/// Unicode codepoint ranges.
+///
+/// Matches are ordered the same way as in `detect`, so this returns
+/// the first element `detect` would return, without allocating. The
+/// ranges are merged as they are read instead of being sorted first,
+/// so overlapping ranges are only counted once if the input is sorted.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges. Overlapping
+///   ranges are merged if the iterator is sorted in ascending order.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns `None` if no language meets the threshold.
+pub fn detect_best<T>(codepoints: T, threshold: f64) -> Option<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    matches(count_distinct(merge_sorted(codepoints)), threshold).min()
+}
+
+/// Detects language support in a font given a list of Unicode
+/// codepoint ranges, calling a function for each match.
+///
+/// This does not allocate, so it is available without the `alloc`
+/// feature. The function is called once for each match, in the same
+/// order as `detect` returns them, after all codepoints are counted.
+/// As in `detect_best`, overlapping ranges are only counted once if
+/// the input is sorted.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges. Overlapping
+///   ranges are merged if the iterator is sorted in ascending order.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `f` - The function to call with each match.
+pub fn detect_each<T, F>(codepoints: T, threshold: f64, mut f: F)
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+    F: FnMut(Match),
+{
+    let counts = count_distinct(merge_sorted(codepoints));
+    let threshold = clamp_threshold(threshold);
+
+    // Sort the indices of the matching languages instead of the matches
+    // themselves, to keep the stack usage small.
+    let mut indices = [0usize; LANGUAGE_COUNT];
+    let mut len = 0;
+
+    for (index, m) in scores(counts).enumerate() {
+        if m.count > 0 && m.score >= threshold {
+            indices[len] = index;
+            len += 1;
+        }
+    }
+
+    let indices = &mut indices[..len];
+    indices
+        .sort_unstable_by(|&a, &b| language_match(a, counts[a]).cmp(&language_match(b, counts[b])));
+
+    for &index in indices.iter() {
+        f(language_match(index, counts[index]));
+    }
+}
+
+/// Detects the languages with the highest scores given a list of
+/// Unicode codepoint ranges.
+///
+/// Only the best `n` matches are kept while scoring, so this avoids
+/// sorting every match when `n` is small. The result is sorted the
+/// same way as in `detect`.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+/// * `n` - The maximum number of matches to return.
+///
+/// Returns a vector of at most `n` language matches.
+#[cfg(feature = "alloc")]
+pub fn detect_top_n<T>(codepoints: T, threshold: f64, n: usize) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut heap = BinaryHeap::with_capacity(n + 1);
+
+    for m in matches(counts(codepoints), threshold) {
+        heap.push(m);
+
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+}
+
+/// Scores every known language given a list of Unicode codepoint
+/// ranges.
+///
+/// Unlike `detect`, no threshold is applied, so languages without any
+/// supported codepoints are returned with a score of 0.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+///
+/// Returns a vector with one match per language, sorted the same way
+/// as in `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_all<T>(codepoints: T) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut matches = scores(counts(codepoints)).collect::<Vec<_>>();
+    matches.sort();
+    matches
+}
+
+/// Returns the languages that score below the threshold by no more
+/// than a margin.
+///
+/// This finds the languages that need only a few more codepoints to
+/// be supported.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   supported. Value must be between 0 and 1.
+/// * `margin` - How far below the threshold a language may score.
+///
+/// Returns the languages with at least one matched codepoint and a
+/// score from `threshold - margin` up to but excluding `threshold`,
+/// closest to the threshold first.
+#[cfg(feature = "alloc")]
+pub fn near_misses<T>(codepoints: T, threshold: f64, margin: f64) -> Vec<NearMiss>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let threshold = clamp_threshold(threshold);
+    let lower = clamp_threshold(threshold - margin);
+
+    let mut misses: Vec<NearMiss> = scores(counts(codepoints))
+        .zip(TOTALS)
+        .filter(|(m, _)| m.count > 0 && m.score >= lower && m.score < threshold)
+        .map(|(m, total)| NearMiss {
+            missing_count: total - m.count,
+            inner: m,
+        })
+        .collect();
+    misses.sort_by(|a, b| a.inner.cmp(&b.inner));
+    misses
+}
+
+/// Splits the languages with at least one matched codepoint into the
+/// ones that meet the threshold and the ones that do not.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   supported. Value must be between 0 and 1.
+///
+/// Returns the supported languages, the same as `detect` returns, and
+/// the partially supported languages, with their scores and counts.
+/// Both are sorted the same way as in `detect`.
+#[cfg(feature = "alloc")]
+pub fn partition<T>(codepoints: T, threshold: f64) -> (Vec<Match>, Vec<Match>)
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let threshold = clamp_threshold(threshold);
+
+    detect_all(codepoints)
+        .into_iter()
+        .filter(|m| m.count > 0)
+        .partition(|m| m.score >= threshold)
+}
+
+/// Checks whether the given codepoint ranges support a language.
+///
+/// # Arguments
+///
+/// * `tag` - The BCP 47 language tag of the language.
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged. Without the `alloc` feature,
+///   only sorted input is merged.
+/// * `threshold` - The minimum score the language must have to be
+///   supported. Value must be between 0 and 1.
+///
+/// Returns `false` if the language is unknown.
+pub fn supports<T>(tag: &str, codepoints: T, threshold: f64) -> bool
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let Some(index) = position(tag) else {
+        return false;
+    };
+
+    let count = distinct(codepoints)
+        .map(|input| intersection(input, RANGES[index]))
+        .fold(0, u32::saturating_add);
+
+    count > 0 && count as f64 / TOTALS[index] as f64 >= clamp_threshold(threshold)
+}
+
+/// Checks whether the given codepoint ranges cover every codepoint
+/// of a language.
+///
+/// This is equivalent to calling `supports` with a threshold of 1.
+pub fn supports_fully<T>(tag: &str, codepoints: T) -> bool
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    supports(tag, codepoints, 1.0)
+}
+
+/// Checks whether the given codepoint ranges support every language
+/// in a list.
+///
+/// The codepoints are counted once for all languages, which is faster
+/// than calling `supports` for each of them.
+///
+/// # Arguments
+///
+/// * `tags` - The BCP 47 language tags of the languages.
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged. Without the `alloc` feature,
+///   only sorted input is merged.
+/// * `threshold` - The minimum score each language must have to be
+///   supported. Value must be between 0 and 1.
+///
+/// Returns `false` if any of the languages is unknown.
+pub fn supports_all<T>(tags: &[&str], codepoints: T, threshold: f64) -> bool
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let threshold = clamp_threshold(threshold);
+    let counts = counts(codepoints);
+
+    tags.iter().all(|tag| {
+        position(tag)
+            .is_some_and(|i| counts[i] > 0 && counts[i] as f64 / TOTALS[i] as f64 >= threshold)
+    })
+}
+
+/// Returns the languages in a list that the given codepoint ranges do
+/// not support.
+///
+/// # Arguments
+///
+/// * `tags` - The BCP 47 language tags of the languages.
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score each language must have to be
+///   supported. Value must be between 0 and 1.
+///
+/// Returns the unknown and unsupported languages in the order of
+/// `tags`. The vector is empty if `supports_all` would return `true`.
+#[cfg(feature = "alloc")]
+pub fn unsupported<'a, T>(tags: &[&'a str], codepoints: T, threshold: f64) -> Vec<Unsupported<'a>>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let threshold = clamp_threshold(threshold);
+    let scores: Vec<Match> = scores(counts(codepoints)).collect();
+
+    tags.iter()
+        .filter_map(|tag| match position(tag) {
+            None => Some(Unsupported::Unknown(tag)),
+            Some(i) if scores[i].count == 0 || scores[i].score < threshold => {
+                Some(Unsupported::BelowThreshold(scores[i].clone()))
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// Returns the codepoints required by a language that are not
+/// covered by the given codepoint ranges.
+///
+/// # Arguments
+///
+/// * `tag` - The BCP 47 language tag of the language.
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+///
+/// Returns the missing codepoints as sorted, coalesced ranges, or
+/// `None` if the language is unknown.
+#[cfg(feature = "alloc")]
+pub fn missing_codepoints<T>(tag: &str, codepoints: T) -> Option<Vec<Range<Codepoint>>>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let index = position(tag)?;
+    let codepoints = normalize_ranges(codepoints);
+
+    Some(difference(RANGES[index], &codepoints))
+}
+
+/// Computes the coverage of a language given a list of Unicode
+/// codepoint ranges.
+///
+/// # Arguments
+///
+/// * `tag` - The BCP 47 language tag of the language.
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+///
+/// Returns `None` if the language is unknown.
+#[cfg(feature = "alloc")]
+pub fn coverage<T>(tag: &str, codepoints: T) -> Option<Coverage>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let index = position(tag)?;
+    let codepoints = normalize_ranges(codepoints);
+    let missing_ranges = difference(RANGES[index], &codepoints);
+
+    let total = TOTALS[index];
+    let missing_count: u32 = missing_ranges
+        .iter()
+        .map(|[lower, upper]| upper - lower + 1)
+        .sum();
+    let matched_count = total - missing_count;
+
+    Some(Coverage {
+        matched_count,
+        missing_count,
+        total,
+        score: ratio(matched_count, total),
+        missing_ranges,
+    })
+}
+
+/// Returns the index of a language for `const_coverage`.
+///
+/// This is a `const fn`, so it can be used in constant expressions.
+///
+/// # Arguments
+///
+/// * `tag` - The BCP 47 language tag of the language.
+///
+/// Returns `None` if the language is unknown.
+pub const fn language_index(tag: &str) -> Option<usize> {
+    let mut index = 0;
+
+    while index < METADATA.len() {
+        if bytes_eq(METADATA[index].tag.as_bytes(), tag.as_bytes()) {
+            return Some(index);
+        }
+        index += 1;
+    }
+
+    None
+}
+
+/// Counts the codepoints of a language matched by the given ranges, in
+/// a constant expression if needed.
+///
+/// This returns the same count as `detect` for the language and can be
+/// evaluated at compile time, for example to assert that a fixed set of
+/// ranges covers a language. It has some limitations compared to
+/// `detect`:
+///
+/// * Every input range is compared with every range of the language,
+///   so it is only suited to small inputs.
+/// * The input ranges must not overlap, or the overlapping codepoints
+///   are counted more than once. They do not need to be sorted.
+/// * It requires the `const-data` feature, which compiles the ranges
+///   as constants even if the `bincode` feature is enabled.
+///
+/// # Arguments
+///
+/// * `codepoints` - The codepoint ranges.
+/// * `index` - The index of the language, as returned by
+///   `language_index`.
+///
+/// # Panics
+///
+/// Panics if the index is out of bounds, which fails the build when
+/// evaluated at compile time.
+#[cfg(feature = "const-data")]
+pub const fn const_coverage(codepoints: &[Range<Codepoint>], index: usize) -> u32 {
+    let ranges = RANGES[index];
+    let mut count: u32 = 0;
+    let mut i = 0;
+
+    while i < codepoints.len() {
+        let mut j = 0;
+
+        while j < ranges.len() {
+            let matched = overlap(codepoints[i], ranges[j]);
+            let surrogates = match overlap_range(codepoints[i], ranges[j]) {
+                Some(range) => overlap(range, SURROGATES),
+                None => 0,
+            };
+            count = count.saturating_add(matched - surrogates);
+            j += 1;
+        }
+        i += 1;
+    }
+
+    count
+}
+
+/// Returns the range of codepoints shared by two ranges, if any.
+#[cfg(feature = "const-data")]
+const fn overlap_range(
+    [a_lower, a_upper]: Range<Codepoint>,
+    [b_lower, b_upper]: Range<Codepoint>,
+) -> Option<Range<Codepoint>> {
+    let lower = if a_lower > b_lower { a_lower } else { b_lower };
+    let upper = if a_upper < b_upper { a_upper } else { b_upper };
+
+    if lower <= upper {
+        Some([lower, upper])
+    } else {
+        None
+    }
+}
+
+/// Counts the codepoints shared by two ranges.
+#[cfg(feature = "const-data")]
+const fn overlap(a: Range<Codepoint>, b: Range<Codepoint>) -> u32 {
+    match overlap_range(a, b) {
+        Some([lower, upper]) => upper - lower + 1,
+        None => 0,
+    }
+}
+
+/// Checks whether two byte strings are equal in a constant expression.
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// Compares the language support of two fonts given their lists of
+/// Unicode codepoint ranges.
+///
+/// # Arguments
+///
+/// * `a` - An iterator of codepoint ranges of the first font.
+/// * `b` - An iterator of codepoint ranges of the second font.
+/// * `threshold` - The minimum score a language must have to be
+///   supported. Value must be between 0 and 1.
+///
+/// Both iterators may be in any order, and overlapping ranges are
+/// merged. Returns the languages supported by only one or by both
+/// fonts, each sorted the same way as in `detect`.
+#[cfg(feature = "alloc")]
+pub fn diff<T, U>(a: T, b: U, threshold: f64) -> CoverageDiff
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+    U: IntoIterator<Item = Range<Codepoint>>,
+{
+    let a = detect(a, threshold);
+    let mut only_b = detect(b, threshold);
+
+    let (both, only_a) = a
+        .into_iter()
+        .partition::<Vec<_>, _>(|m| only_b.iter().any(|n| n.tag == m.tag));
+    only_b.retain(|m| !both.iter().any(|n| n.tag == m.tag));
+
+    CoverageDiff {
+        only_a,
+        only_b,
+        both,
+    }
+}
+
+/// Detects the languages supported by a family of fonts given the
+/// codepoint ranges of each font.
+///
+/// The ranges of all fonts are combined, with each codepoint counted
+/// once, and detection is run once on the combined set.
+///
+/// # Arguments
+///
+/// * `fonts` - The codepoint ranges of each font. The ranges of each
+///   font may be in any order and may overlap.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`.
+#[cfg(feature = "alloc")]
+pub fn union_coverage<T>(fonts: T, threshold: f64) -> Vec<Match>
+where
+    T: IntoIterator<Item = Vec<Range<Codepoint>>>,
+{
+    let codepoints = fonts.into_iter().fold(Vec::new(), |codepoints, font| {
+        ranges::union(&codepoints, &normalize_ranges(font))
+    });
+
+    detect(codepoints, threshold)
+}
+
+/// Detects the languages supported by a family of fonts and which of
+/// the fonts support each of them on their own.
+///
+/// # Arguments
+///
+/// * `fonts` - The name and codepoint ranges of each font. The ranges
+///   of each font may be in any order and may overlap.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns the same matches as `union_coverage`, each with the names
+/// of the fonts that meet the threshold by themselves, in the order of
+/// `fonts`. The list is empty for a language that is only supported by
+/// the fonts combined.
+#[cfg(feature = "alloc")]
+pub fn attribute_coverage<'a>(
+    fonts: &[(&'a str, Vec<Range<Codepoint>>)],
+    threshold: f64,
+) -> Vec<(Match, Vec<&'a str>)> {
+    let per_font: Vec<Vec<Match>> = fonts
+        .iter()
+        .map(|(_, ranges)| detect(ranges.iter().copied(), threshold))
+        .collect();
+
+    union_coverage(fonts.iter().map(|(_, ranges)| ranges.clone()), threshold)
+        .into_iter()
+        .map(|m| {
+            let names = fonts
+                .iter()
+                .zip(&per_font)
+                .filter(|(_, matches)| matches.iter().any(|n| n.tag == m.tag))
+                .map(|((name, _), _)| *name)
+                .collect();
+            (m, names)
+        })
+        .collect()
+}
+
+/// Returns the codepoints that are not required by any known language,
+/// such as symbols or private use characters.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///
+/// Returns the unmatched codepoints as sorted, coalesced ranges.
+#[cfg(feature = "alloc")]
+pub fn unmatched_codepoints<T>(codepoints: T) -> Vec<Range<Codepoint>>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    #[cfg(feature = "std")]
+    let required = index::required();
+    #[cfg(not(feature = "std"))]
+    let required = &normalize_ranges(RANGES.iter().flat_map(|ranges| ranges.iter().copied()));
+
+    difference(&normalize_ranges(codepoints), required)
+}
+
+/// Finds a small set of languages that explains the codepoints of a
+/// font.
+///
+/// Languages are chosen greedily: each step picks the language that
+/// requires the most codepoints not explained by the languages chosen
+/// before it, until no remaining codepoint is required by any
+/// language. This does not always find the smallest possible set.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   chosen. Value must be between 0 and 1.
+///
+/// Returns the chosen languages and the codepoints they leave
+/// unexplained.
+#[cfg(feature = "alloc")]
+pub fn explain_coverage<T>(codepoints: T, threshold: f64) -> Explanation
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut unexplained = normalize_ranges(codepoints);
+    let mut candidates = detect(unexplained.iter().copied(), threshold);
+    let mut matches = Vec::new();
+
+    while !unexplained.is_empty() {
+        let best = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let ranges = RANGES[position(m.tag).unwrap()];
+                let count = unexplained.iter().fold(0u32, |count, range| {
+                    count.saturating_add(intersection(*range, ranges))
+                });
+                (i, count)
+            })
+            .filter(|(_, count)| *count > 0)
+            .min_by_key(|(_, count)| Reverse(*count));
+
+        let Some((i, _)) = best else {
+            break;
+        };
+
+        let m = candidates.remove(i);
+        unexplained = difference(&unexplained, RANGES[position(m.tag).unwrap()]);
+        matches.push(m);
+    }
+
+    Explanation {
+        matches,
+        unexplained,
+    }
+}
+
+/// Detects language support given a string of text.
+///
+/// Each distinct Unicode scalar value in `text` is counted once, no
+/// matter how often it occurs, so the `count` of each match is the
+/// number of distinct codepoints matched. Combining marks, punctuation
+/// and other non-letter characters are counted the same as letters.
+///
+/// # Arguments
+///
+/// * `text` - The text to detect languages in.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches.
+#[cfg(feature = "alloc")]
+pub fn detect_str(text: &str, threshold: f64) -> Vec<Match> {
+    detect_codepoints(text.chars().map(Codepoint::from), threshold)
+}
+
+/// Detects language support given a string of text, normalized to
+/// Unicode Normalization Form C first.
+///
+/// Normalizing composes base characters and combining marks into
+/// precomposed characters where possible, so `e` followed by U+0301
+/// COMBINING ACUTE ACCENT is counted as `é`. This changes the counts
+/// for scripts written with combining marks. Use `detect_str` to count
+/// the codepoints of the text as they are.
+///
+/// # Arguments
+///
+/// * `text` - The text to detect languages in.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches.
+#[cfg(feature = "unicode-normalization")]
+pub fn detect_str_nfc(text: &str, threshold: f64) -> Vec<Match> {
+    use unicode_normalization::UnicodeNormalization;
+
+    detect_codepoints(text.nfc().map(Codepoint::from), threshold)
+}
+
+/// Detects language support given a string of text, counting only the
+/// first codepoint of each grapheme cluster.
+///
+/// The text is split into extended grapheme clusters as defined by
+/// Unicode Standard Annex #29, and only the first codepoint of each
+/// cluster, usually its base character, is counted. Combining marks,
+/// variation selectors and the later parts of emoji sequences are not
+/// counted at all. Each distinct base codepoint is counted once, the
+/// same as in `detect_str`.
+///
+/// This is meant for analyzing text. To find the languages a font
+/// supports, every codepoint matters, so use `detect` instead.
+///
+/// # Arguments
+///
+/// * `text` - The text to detect languages in.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches.
+#[cfg(feature = "unicode-segmentation")]
+pub fn detect_graphemes(text: &str, threshold: f64) -> Vec<Match> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    detect_codepoints(
+        text.graphemes(true)
+            .filter_map(|grapheme| grapheme.chars().next())
+            .map(Codepoint::from),
+        threshold,
+    )
+}
+
+/// Detects language support given a list of individual Unicode
+/// codepoints.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoints. The iterator may be
+///   unsorted and contain duplicates.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches.
+#[cfg(feature = "alloc")]
+pub fn detect_codepoints<T>(codepoints: T, threshold: f64) -> Vec<Match>
+where
+    T: IntoIterator<Item = Codepoint>,
+{
+    detect(coalesce(codepoints), threshold)
+}
+
+/// Detects language support given a list of character ranges.
+///
+/// This is the same as `detect`, with each `[lower, upper]` range of
+/// characters converted to a codepoint range.
+///
+/// # Arguments
+///
+/// * `ranges` - An iterator of character ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`.
+#[cfg(feature = "alloc")]
+pub fn detect_chars<T>(ranges: T, threshold: f64) -> Vec<Match>
+where
+    T: IntoIterator<Item = Range<char>>,
+{
+    detect(
+        ranges
+            .into_iter()
+            .map(|[lower, upper]| [Codepoint::from(lower), Codepoint::from(upper)]),
+        threshold,
+    )
+}
+
+/// Converts a list of individual Unicode codepoints into codepoint
+/// ranges.
+///
+/// The codepoints are sorted and deduplicated, and consecutive
+/// codepoints are merged into a single range, so the result can be
+/// passed to `detect` directly.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoints. The iterator may be
+///   unsorted and contain duplicates.
+///
+/// Returns a vector of sorted, non-overlapping and non-adjacent
+/// codepoint ranges.
+#[cfg(feature = "alloc")]
+pub fn coalesce<T>(codepoints: T) -> Vec<Range<Codepoint>>
+where
+    T: IntoIterator<Item = Codepoint>,
+{
+    let mut codepoints: Vec<Codepoint> = codepoints.into_iter().collect();
+    codepoints.sort_unstable();
+    codepoints.dedup();
+
+    let mut ranges: Vec<Range<Codepoint>> = Vec::new();
+
+    for codepoint in codepoints {
+        push_range(&mut ranges, [codepoint, codepoint]);
+    }
+
+    ranges
+}
+
+/// Converts a range of characters, such as `'a'..='z'`, into a
+/// codepoint range.
+///
+/// The range may span the surrogate codepoints, which are not
+/// characters. This is not a problem, as surrogates are never counted
+/// as matched.
+///
+/// # Arguments
+///
+/// * `range` - An inclusive range of characters.
+///
+/// Returns the codepoint range. An empty character range, whose start
+/// is after its end, becomes a reversed range, which matches nothing.
+pub fn char_range(range: RangeInclusive<char>) -> Range<Codepoint> {
+    [
+        Codepoint::from(*range.start()),
+        Codepoint::from(*range.end()),
+    ]
+}
+
+/// Detects script support in a font given a list of Unicode codepoint
+/// ranges.
+///
+/// The codepoints of all languages written in a script are combined,
+/// with each codepoint counted once, and the input is scored against
+/// the combined set.
+///
+/// # Arguments
+///
+/// * `codepoints` - An iterator of codepoint ranges, in any order.
+///   Overlapping ranges are merged.
+/// * `threshold` - The minimum score a script must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of script matches, sorted by score in descending
+/// order and then by script in ascending order.
+#[cfg(feature = "alloc")]
+pub fn detect_scripts<T>(codepoints: T, threshold: f64) -> Vec<ScriptMatch>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let threshold = clamp_threshold(threshold);
+    let codepoints = normalize_ranges(codepoints);
+
+    let mut scripts: Vec<&'static str> = METADATA.iter().map(|m| m.script).collect();
+    scripts.sort_unstable();
+    scripts.dedup();
+
+    let mut matches: Vec<ScriptMatch> = scripts
+        .into_iter()
+        .filter_map(|script| {
+            let ranges = normalize_ranges(
+                METADATA
+                    .iter()
+                    .zip(RANGES)
+                    .filter(|(metadata, _)| metadata.script == script)
+                    .flat_map(|(_, ranges)| ranges.iter().copied()),
+            );
+
+            let total = ranges.iter().fold(0u32, |total, [lower, upper]| {
+                total.saturating_add(upper - lower + 1)
+            });
+            let count = codepoints.iter().fold(0u32, |count, range| {
+                count.saturating_add(intersection(*range, &ranges))
+            });
+            let score = ratio(count, total);
+
+            (score >= threshold && count > 0).then_some(ScriptMatch {
+                script,
+                count,
+                total,
+                score,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.script.cmp(b.script))
+    });
+    matches
+}
+
+/// Counts the number of codepoints matched for each language.
+#[cfg(not(feature = "rayon"))]
+fn counts<T>(codepoints: T) -> [u32; LANGUAGE_COUNT]
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    count_distinct(distinct(codepoints))
+}
+
+/// Counts the number of codepoints matched for each language, given
+/// ranges that contain every codepoint at most once.
+fn count_distinct<I>(codepoints: I) -> [u32; LANGUAGE_COUNT]
+where
+    I: Iterator<Item = Range<Codepoint>>,
+{
+    let mut counts = [0u32; LANGUAGE_COUNT];
+
+    for input in codepoints {
+        for (count, ranges) in counts.iter_mut().zip(RANGES) {
+            *count = count.saturating_add(intersection(input, ranges));
+        }
+    }
+
+    counts
+}
+
+/// Counts the number of codepoints matched for each language, with
+/// every language counted on the Rayon thread pool.
+#[cfg(feature = "rayon")]
+fn counts<T>(codepoints: T) -> [u32; LANGUAGE_COUNT]
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    use rayon::prelude::*;
+
+    let codepoints = normalize_ranges(codepoints);
+    let mut counts = [0u32; LANGUAGE_COUNT];
+
+    counts
+        .par_iter_mut()
+        .zip(RANGES.par_iter())
+        .for_each(|(count, ranges)| {
+            *count = codepoints
+                .iter()
+                .map(|input| intersection(*input, ranges))
+                .fold(0, u32::saturating_add);
+        });
+
+    counts
+}
+
+/// Returns the languages that meet the threshold, in the order of the
+/// generated data.
+fn matches(counts: [u32; LANGUAGE_COUNT], threshold: f64) -> impl Iterator<Item = Match> {
+    let threshold = clamp_threshold(threshold);
+
+    scores(counts).filter(move |m| m.score >= threshold && m.count > 0)
+}
+
+/// Scores every language from its codepoint count.
+fn scores(counts: [u32; LANGUAGE_COUNT]) -> impl Iterator<Item = Match> {
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, count)| language_match(index, count))
+}
+
+/// Scores a language from its index in the generated data and its
+/// codepoint count.
+fn language_match(index: usize, count: u32) -> Match {
+    let metadata = &METADATA[index];
+    // Overlapping input is merged before counting, but without `alloc`
+    // only consecutive ranges are, so the count is capped as well.
+    let count = cmp::min(count, TOTALS[index]);
+
+    Match {
+        tag: metadata.tag,
+        name: metadata.name,
+        native: metadata.native_name,
+        script: metadata.script,
+        count,
+        total: TOTALS[index],
+        score: ratio(count, TOTALS[index]),
+        speakers: metadata.speakers,
+    }
+}
+
+/// Clamps a threshold to between 0 and 1, treating `NaN` as 0.
+fn clamp_threshold(threshold: f64) -> f64 {
+    if threshold.is_nan() {
+        0.0
+    } else {
+        threshold.clamp(0.0, 1.0)
+    }
+}
+
+/// Divides a codepoint count by a language's total. A language without
+/// codepoints scores 0 rather than `NaN`, so matches always sort.
+fn ratio(count: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Returns the number of auxiliary codepoints of the language at
+/// `index` in the sorted, coalesced input.
+#[cfg(feature = "alloc")]
+fn aux_count(index: usize, input: &[Range<Codepoint>]) -> u32 {
+    input
+        .iter()
+        .map(|&range| intersection(range, AUX_RANGES[index]))
+        .sum()
+}
+
+/// Scores `count` of `total` main and `aux_count` of `aux_total`
+/// auxiliary codepoints, counting each auxiliary codepoint as
+/// `1 / (aux_total + 1)` of a main codepoint.
+#[cfg(feature = "alloc")]
+fn weighted_score(count: u32, total: u32, aux_count: u32, aux_total: u32) -> f64 {
+    let weight = 1.0 / (aux_total as f64 + 1.0);
+    let total = total as f64 + aux_total as f64 * weight;
+
+    if total == 0.0 {
+        0.0
+    } else {
+        (count as f64 + aux_count as f64 * weight) / total
+    }
+}
+
+/// Returns the index of a language in the generated data.
+fn position(tag: &str) -> Option<usize> {
+    METADATA.iter().position(|metadata| metadata.tag == tag)
+}
+
+/// The UTF-16 surrogate codepoints, which are not Unicode scalar
+/// values.
+const SURROGATES: Range<Codepoint> = [0xD800, 0xDFFF];
+
+/// Counts the codepoints of an input range that are contained in a
+/// language's ranges. The language's ranges must be sorted in
+/// ascending order and must not overlap. A reversed input range is
+/// empty, and surrogate codepoints are never counted.
+fn intersection([input_lower, input_upper]: Range<Codepoint>, ranges: &[Range<Codepoint>]) -> u32 {
+    let [surrogate_lower, surrogate_upper] = SURROGATES;
+
+    if input_upper < surrogate_lower || input_lower > surrogate_upper {
+        return count_overlap([input_lower, input_upper], ranges);
+    }
+
+    // Only count the parts of the input below and above the surrogates.
+    let below = if input_lower < surrogate_lower {
+        count_overlap([input_lower, surrogate_lower - 1], ranges)
+    } else {
+        0
+    };
+
+    let above = if input_upper > surrogate_upper {
+        count_overlap([surrogate_upper + 1, input_upper], ranges)
+    } else {
+        0
+    };
+
+    below + above
+}
+
+/// Counts the codepoints of an input range that are contained in a
+/// language's ranges. The language's ranges must be sorted in
+/// ascending order and must not overlap. A reversed input range is
+/// empty.
+fn count_overlap([input_lower, input_upper]: Range<Codepoint>, ranges: &[Range<Codepoint>]) -> u32 {
+    if input_lower > input_upper {
+        return 0;
+    }
+
+    // Skip the ranges that end before the input starts. As the ranges
+    // are sorted and do not overlap, the ranges that end before the
+    // input form a prefix, and once a range starts after the input ends
+    // so do all following ones. Adjacent ranges are each counted on
+    // their own, so they do not need to be merged.
+    let start = ranges.partition_point(|[_, range_upper]| *range_upper < input_lower);
+
+    ranges[start..]
+        .iter()
+        .take_while(|[range_lower, _]| *range_lower <= input_upper)
+        .map(|[range_lower, range_upper]| {
+            cmp::min(input_upper, *range_upper) - cmp::max(input_lower, *range_lower) + 1
+        })
+        .sum()
+}
+
+/// Returns input ranges that contain every codepoint at most once, so
+/// overlapping input is not counted twice. Reversed ranges are removed.
+///
+/// With the `alloc` feature the ranges are sorted and merged, which
+/// handles any input. Without it, each range is only merged with the
+/// ranges right before it, which handles sorted input.
+fn distinct<T>(codepoints: T) -> impl Iterator<Item = Range<Codepoint>>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    #[cfg(feature = "alloc")]
+    return normalize_ranges(codepoints).into_iter();
+
+    #[cfg(not(feature = "alloc"))]
+    merge_sorted(codepoints)
+}
+
+/// Returns input ranges with consecutive overlapping and adjacent
+/// ranges merged, without allocating. Every codepoint is contained at
+/// most once if the input is sorted. Reversed ranges are removed.
+fn merge_sorted<T>(codepoints: T) -> MergeOverlapping<T::IntoIter>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    MergeOverlapping {
+        ranges: codepoints.into_iter(),
+        pending: None,
+    }
+}
+
+/// An iterator merging overlapping and adjacent consecutive ranges.
+struct MergeOverlapping<I> {
+    ranges: I,
+    pending: Option<Range<Codepoint>>,
+}
+
+impl<I: Iterator<Item = Range<Codepoint>>> Iterator for MergeOverlapping<I> {
+    type Item = Range<Codepoint>;
+
+    fn next(&mut self) -> Option<Range<Codepoint>> {
+        for [lower, upper] in self.ranges.by_ref() {
+            if lower > upper {
+                continue;
+            }
+
+            match self.pending {
+                Some([pending_lower, pending_upper])
+                    if lower <= pending_upper.saturating_add(1)
+                        && upper.saturating_add(1) >= pending_lower =>
+                {
+                    self.pending = Some([
+                        cmp::min(lower, pending_lower),
+                        cmp::max(upper, pending_upper),
+                    ]);
+                }
+                pending => {
+                    self.pending = Some([lower, upper]);
+                    if pending.is_some() {
+                        return pending;
+                    }
+                }
+            }
+        }
+
+        self.pending.take()
+    }
+}
+
+/// Counts the codepoints in sorted, coalesced ranges, leaving out
+/// surrogates the same way as when counting matched codepoints.
+#[cfg(feature = "alloc")]
+fn codepoint_count(ranges: &[Range<Codepoint>]) -> u32 {
+    ranges.iter().fold(0u32, |count, range| {
+        count.saturating_add(intersection(*range, &[[0, Codepoint::MAX]]))
+    })
+}
+
+/// Sorts ranges and merges overlapping and adjacent ranges. Reversed
+/// ranges are removed.
+#[cfg(feature = "alloc")]
+fn normalize_ranges<T>(ranges: T) -> Vec<Range<Codepoint>>
+where
+    T: IntoIterator<Item = Range<Codepoint>>,
+{
+    let mut ranges: Vec<Range<Codepoint>> = ranges
+        .into_iter()
+        .filter(|[lower, upper]| lower <= upper)
+        .collect();
+    ranges.sort_unstable_by_key(|[lower, _]| *lower);
+
+    let mut result = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        push_range(&mut result, range);
+    }
+
+    result
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use proptest::prelude::*;
+
+    #[test]
+    fn it_returns_all_languages() {
+        let result: Vec<LanguageInfo> = languages().collect();
+        assert_eq!(result.len(), 5);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].name, "test1");
+        assert_eq!(result[0].native, "ntest1");
+        assert_eq!(result[0].script, "Latn");
+        assert_eq!(result[0].total, 3);
+        assert_eq!(result[4].tag, "t5");
+        assert_eq!(result[4].total, 1);
+    }
+
+    #[test]
+    fn it_returns_a_language_by_tag() {
+        let result = language_by_tag("t2").unwrap();
+        assert_eq!(result.tag, "t2");
+        assert_eq!(result.name, "test2");
+        assert_eq!(result.native, "ntest2");
+        assert_eq!(result.script, "Latn");
+        assert_eq!(result.total, 3);
+    }
+
+    #[test]
+    fn it_does_not_return_an_unknown_language() {
+        assert!(language_by_tag("xx").is_none());
+        assert!(language_by_tag("").is_none());
+    }
+
+    #[test]
+    fn it_returns_the_required_count() {
+        assert_eq!(required_count("t1"), Some(3));
+        assert_eq!(required_count("t4"), Some(1));
+        assert_eq!(required_count("xx"), None);
+    }
+
+    #[test]
+    fn it_returns_the_ranges_of_a_language() {
+        assert_eq!(ranges_for("t3"), Some(&[[7, 9]][..]));
+        assert_eq!(ranges_for("t5"), Some(&[[16, 16]][..]));
+        assert_eq!(ranges_for("xx"), None);
+    }
+
+    #[test]
+    fn it_displays_a_match() {
+        let result = detect([[1, 2]], 0.0);
+        assert_eq!(result[0].to_string(), "test1 (t1) — 66.7%");
+
+        let result = detect([[1, 3]], 0.0);
+        assert_eq!(result[0].to_string(), "test1 (t1) — 100.0%");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_a_match() {
+        let result = detect([[1, 3]], 0.0);
+        assert_eq!(
+            serde_json::to_string(&result[0]).unwrap(),
+            r#"{"tag":"t1","name":"test1","native":"ntest1","script":"Latn","count":3,"total":3,"score":1.0,"speakers":100}"#
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_a_detailed_match() {
+        let result = detect_detailed([[1, 3]], 0.0);
+        assert_eq!(
+            serde_json::to_string(&result[0]).unwrap(),
+            r#"{"tag":"t1","name":"test1","native":"ntest1","script":"Latn","count":3,"total":3,"score":1.0,"speakers":100,"matched_ranges":[[1,3]]}"#
+        );
+    }
+
+    #[test]
+    fn it_returns_an_empty_array() {
+        let result = detect([], 0.5);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn it_takes_a_vector() {
+        let codepoints = vec![[1, 3]];
+
+        let result = detect(codepoints, 1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].name, "test1");
+    }
+
+    #[test]
+    fn it_takes_an_array() {
+        let codepoints = [[1, 3]];
+
+        let result = detect(codepoints, 1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].name, "test1");
+    }
+
+    #[test]
+    fn it_returns_an_empty_array_with_an_invalid_codepoint() {
+        let result = detect([[256, 256]], 0.5);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn it_returns_the_test_language() {
+        let result = detect([[1, 1]], 0.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].name, "test1")
+    }
+
+    #[test]
+    fn it_does_not_return_if_threshold_not_met() {
+        let result = detect([[1, 2]], 1.0);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn it_returns_if_threshold_is_met() {
+        let result = detect([[1, 3]], 1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].name, "test1");
+    }
+
+    #[test]
+    fn it_compares_matches() {
+        let result = detect([[1, 2]], 0.0);
+        assert_eq!(
+            result,
+            vec![Match {
+                tag: "t1",
+                name: "test1",
+                native: "ntest1",
+                script: "Latn",
+                count: 2,
+                total: 3,
+                score: 2.0 / 3.0,
+                speakers: Some(100),
+            }]
+        );
+        assert_eq!(result[0].clone(), result[0]);
+        assert_ne!(result[0], detect([[1, 3]], 0.0)[0]);
+    }
+
+    #[test]
+    fn it_orders_matches_by_score_and_tag() {
+        let result = detect([[1, 1], [4, 6], [8, 8]], 0.0);
+        let mut sorted = result.clone();
+        sorted.reverse();
+        sorted.sort();
+        assert_eq!(sorted, result);
+        assert!(result[0] < result[1]);
+        assert_eq!(result[0].tag, "t2");
+        assert_eq!(result[1].tag, "t4");
+        assert_eq!(result[2].tag, "t1");
+        assert_eq!(result[3].tag, "t3");
+    }
+
+    #[test]
+    fn it_breaks_score_ties_by_tag() {
+        let result = detect([[1, 1], [4, 4], [7, 7]], 0.0);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[1].tag, "t2");
+        assert_eq!(result[2].tag, "t3");
+
+        let m = |tag| Match {
+            tag,
+            name: "",
+            native: "",
+            script: "",
+            count: 1,
+            total: 2,
+            score: 0.5,
+            speakers: None,
+        };
+        let mut result = vec![m("nl"), m("de"), m("en")];
+        result.sort();
+        assert_eq!(result, vec![m("de"), m("en"), m("nl")]);
+    }
+
+    #[test]
+    fn it_returns_the_iso_639_1_code() {
+        let m = |tag| Match {
+            tag,
+            ..detect([[1, 1]], 0.0).remove(0)
+        };
+
+        assert_eq!(m("en").iso_639_1(), Some("en"));
+        assert_eq!(m("sr-Latn").iso_639_1(), Some("sr"));
+        assert_eq!(m("pt-BR").iso_639_1(), Some("pt"));
+        assert_eq!(m("haw").iso_639_1(), None);
+        assert_eq!(m("t1").iso_639_1(), None);
+    }
+
+    #[test]
+    fn it_breaks_score_ties_by_count() {
+        let result = detect([[1, 3], [8, 8]], 0.5);
+        assert_eq!(
+            result.iter().map(|m| (m.tag, m.count)).collect::<Vec<_>>(),
+            [("t1", 3), ("t4", 1)]
+        );
+
+        let m = |tag, count, total| Match {
+            tag,
+            count,
+            total,
+            score: 0.5,
+            ..detect([[1, 1]], 0.0).remove(0)
+        };
+        let mut result = [m("aa", 1, 2), m("zz", 4, 8), m("mm", 4, 8)];
+        result.sort();
+        assert_eq!(
+            result.iter().map(|m| m.tag).collect::<Vec<_>>(),
+            ["mm", "zz", "aa"]
+        );
+        assert_ne!(m("aa", 1, 2), m("aa", 2, 4));
+    }
+
+    #[test]
+    fn it_orders_nan_scores_last() {
+        let mut a = detect([[1, 1]], 0.0).remove(0);
+        let mut b = a.clone();
+        a.score = f64::NAN;
+        b.tag = "t9";
+        assert!(b < a);
+        assert_eq!(a, a.clone());
+    }
+
+    #[test]
+    fn it_hashes_matches_consistently_with_equality() {
+        use std::collections::HashSet;
+
+        let mut set: HashSet<Match> = detect([[1, 9]], 0.0).into_iter().collect();
+        assert_eq!(set.len(), 4);
+        assert!(!set.insert(detect([[1, 3]], 0.0).remove(0)));
+        assert!(set.insert(detect([[1, 2]], 0.0).remove(0)));
+
+        let nan = |bits| Match {
+            score: f64::from_bits(bits),
+            ..detect([[1, 1]], 0.0).remove(0)
+        };
+        let (a, b) = (nan(f64::NAN.to_bits()), nan(f64::NAN.to_bits() | 1));
+        assert_eq!(a, b);
+        assert!(HashSet::from([a]).contains(&b));
+    }
+
+    #[test]
+    fn it_sorts_zero_total_scores_without_panicking() {
+        let m = |tag, count: u32, total: u32| Match {
+            tag,
+            count,
+            total,
+            score: count as f64 / total as f64,
+            ..detect([[1, 1]], 0.0).remove(0)
+        };
+        let mut result = [m("t9", 0, 0), m("t1", 1, 3), m("t8", 0, 0)];
+        result.sort();
+        assert_eq!(
+            result.iter().map(|m| m.tag).collect::<Vec<_>>(),
+            ["t1", "t8", "t9"]
+        );
+    }
+
+    #[test]
+    fn it_filters_by_min_count() {
+        let result = detect_with([[1, 9]], 1.0, 2);
+        let tags = result.iter().map(|m| m.tag).collect::<Vec<_>>();
+        assert_eq!(tags, ["t1", "t2", "t3"]);
+    }
+
+    #[test]
+    fn it_applies_threshold_and_min_count() {
+        let result = detect_with([[1, 1], [4, 6], [8, 8]], 0.5, 1);
+        let tags = result.iter().map(|m| m.tag).collect::<Vec<_>>();
+        assert_eq!(tags, ["t2", "t4"]);
+        assert_eq!(detect_with([[1, 1], [4, 6], [8, 8]], 0.0, 0).len(), 4);
+    }
+
+    #[test]
+    fn it_scores_by_completeness() {
+        let input = [[1, 1], [4, 6], [8, 9], [16, 16]];
+        assert_eq!(
+            detect_with_mode(input, 0.0, ScoreMode::Completeness),
+            detect(input, 0.0)
+        );
+    }
+
+    #[test]
+    fn it_scores_by_input_coverage() {
+        let result = detect_with_mode([[1, 6], [10, 11]], 0.0, ScoreMode::InputCoverage);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[0].score, 3.0 / 8.0);
+        assert_eq!(result[1].tag, "t2");
+        assert!(detect_with_mode([[1, 6], [10, 11]], 0.5, ScoreMode::InputCoverage).is_empty());
+    }
+
+    #[test]
+    fn it_scores_by_jaccard_index() {
+        let result = detect_with_mode([[1, 2], [7, 12]], 0.0, ScoreMode::Jaccard);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].tag, "t3");
+        assert_eq!(result[0].score, 3.0 / 8.0);
+        assert_eq!(result[1].tag, "t1");
+        assert_eq!(result[1].score, 2.0 / 9.0);
+        assert_eq!(result[2].tag, "t4");
+        assert_eq!(result[2].score, 1.0 / 8.0);
+    }
+
+    #[test]
+    fn it_scores_by_range_coverage() {
+        let result = detect_with_mode([[1, 1], [8, 8]], 1.0, ScoreMode::RangeCoverage);
+        let tags = result.iter().map(|m| m.tag).collect::<Vec<_>>();
+        assert_eq!(tags, ["t1", "t3", "t4"]);
+        assert!(result.iter().all(|m| m.score == 1.0 && m.count == 1));
+        assert!(detect_with_mode([[10, 15]], 0.0, ScoreMode::RangeCoverage).is_empty());
+    }
+
+    #[test]
+    fn it_weights_main_over_auxiliary_codepoints() {
+        let score = |input: &[Range<Codepoint>]| {
+            detect_with_mode(input.iter().copied(), 0.0, ScoreMode::MainWeighted)
+                .into_iter()
+                .find(|m| m.tag == "t1")
+                .unwrap()
+                .score
+        };
+        assert!((score(&[[1, 3]]) - 9.0 / 11.0).abs() < 1e-9);
+        assert!((score(&[[1, 2], [10, 11]]) - 8.0 / 11.0).abs() < 1e-9);
+        assert_eq!(score(&[[1, 3], [10, 11]]), 1.0);
+
+        let result = detect_with_mode([[4, 6]], 1.0, ScoreMode::MainWeighted);
+        assert_eq!(result.iter().map(|m| m.tag).collect::<Vec<_>>(), ["t2"]);
+        assert!(detect_with_mode([[10, 11]], 0.0, ScoreMode::MainWeighted).is_empty());
+    }
+
+    #[test]
+    fn it_scores_main_and_auxiliary_codepoints_separately() {
+        let result = detect_exemplars([[10, 10], [1, 2]], 0.0);
+        assert_eq!(result.len(), 1);
+        let m = &result[0];
+        assert_eq!(m.inner.tag, "t1");
+        assert_eq!((m.inner.count, m.aux_count, m.aux_total), (2, 1, 2));
+        assert_eq!(m.main_score, 2.0 / 3.0);
+        assert_eq!(m.aux_score, Some(0.5));
+        assert!((m.inner.score - 7.0 / 11.0).abs() < 1e-9);
+
+        let result = detect_exemplars([[4, 6]], 1.0);
+        assert_eq!(result[0].inner.tag, "t2");
+        assert_eq!((result[0].main_score, result[0].aux_score), (1.0, None));
+        assert!(detect_exemplars([[1, 2]], 0.7).is_empty());
+    }
+
+    #[test]
+    fn it_returns_matched_ranges() {
+        let result = detect_detailed([[2, 5], [8, 8]], 0.0);
+        assert_eq!(result.len(), 4);
+        assert_eq!(result[0].inner.tag, "t4");
+        assert_eq!(result[0].matched_ranges, vec![[8, 8]]);
+        assert_eq!(result[1].inner.tag, "t1");
+        assert_eq!(result[1].matched_ranges, vec![[2, 3]]);
+        assert_eq!(result[2].inner.tag, "t2");
+        assert_eq!(result[2].matched_ranges, vec![[4, 5]]);
+        assert_eq!(result[3].inner.tag, "t3");
+
+        let matches = result.into_iter().map(|m| m.inner).collect::<Vec<_>>();
+        assert_eq!(matches, detect([[2, 5], [8, 8]], 0.0));
+    }
+
+    #[test]
+    fn it_returns_matched_codepoints() {
+        let result = detect_detailed([[2, 5], [8, 8]], 0.0);
+        let t2 = result.iter().find(|m| m.inner.tag == "t2").unwrap();
+        assert_eq!(t2.matched_codepoints().collect::<Vec<_>>(), [4, 5]);
+
+        let huge = DetailedMatch {
+            inner: t2.inner.clone(),
+            matched_ranges: vec![[0, Codepoint::MAX]],
+        };
+        assert_eq!(huge.matched_codepoints().nth(10), Some(10));
+    }
+
+    #[test]
+    fn it_returns_language_indices() {
+        const T3: Option<usize> = language_index("t3");
+        const _: () = assert!(matches!(T3, Some(2)));
+
+        assert_eq!(language_index("t1"), Some(0));
+        assert_eq!(language_index("t"), None);
+    }
+
+    #[cfg(feature = "const-data")]
+    #[test]
+    fn it_counts_coverage_in_const_expressions() {
+        const T3: usize = match language_index("t3") {
+            Some(index) => index,
+            None => panic!("unknown language"),
+        };
+        const COUNT: u32 = const_coverage(&[[8, 12], [1, 7]], T3);
+        const _: () = assert!(COUNT == 3);
+
+        for input in [[[0, 0xFFFF]], [[5, 8]], [[0xD700, 0xE000]]] {
+            for m in detect_all(input) {
+                let index = language_index(m.tag).unwrap();
+                assert_eq!(const_coverage(&input, index), m.count);
+            }
+        }
+    }
+
+    #[test]
+    fn it_sorts_matches() {
+        let input = [[1, 3], [5, 6], [8, 8]];
+        let tags = |sort| {
+            detect_sorted(input, 0.0, sort)
+                .iter()
+                .map(|m| m.tag)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tags(SortBy::Score), ["t1", "t4", "t2", "t3"]);
+        assert_eq!(tags(SortBy::Count), ["t1", "t2", "t4", "t3"]);
+        assert_eq!(tags(SortBy::Name), ["t1", "t2", "t3", "t4"]);
+        assert_eq!(tags(SortBy::NativeName), ["t1", "t2", "t3", "t4"]);
+        assert_eq!(tags(SortBy::Speakers), ["t3", "t1", "t4", "t2"]);
+        assert_eq!(tags(SortBy::default()), tags(SortBy::Score));
+    }
+
+    #[test]
+    fn it_finds_languages_by_name() {
+        let tags = |query| {
+            find_languages(query)
+                .iter()
+                .map(|(language, field)| (language.tag, *field))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(tags("TEST3"), [("t3", NameField::Name)]);
+        assert_eq!(tags("ntest4"), [("t4", NameField::Native)]);
+        assert_eq!(tags("st5"), [("t5", NameField::Name)]);
+        assert_eq!(tags("").len(), 5);
+        assert!(tags("test6").is_empty());
+    }
+
+    #[test]
+    fn it_folds_case() {
+        #[cfg(feature = "unicode-normalization")]
+        assert_eq!(fold_case("E\u{301}A"), ['é', 'a']);
+        #[cfg(not(feature = "unicode-normalization"))]
+        assert_eq!(fold_case("ÉA"), ['É', 'a']);
+    }
+
+    #[test]
+    fn it_compares_names() {
+        let mut collation = Collation::default();
+        let mut names = ["Peng", "Peña", "Ernie", "Émile"];
+        names.sort_by(|a, b| collation.compare(a, b));
+
+        #[cfg(feature = "feruca")]
+        assert_eq!(names, ["Émile", "Ernie", "Peña", "Peng"]);
+        #[cfg(not(feature = "feruca"))]
+        assert_eq!(names, ["Ernie", "Peng", "Peña", "Émile"]);
+    }
+
+    #[test]
+    fn it_returns_if_threshold_is_partially_met() {
+        let result = detect([[1, 2]], 0.6);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].name, "test1");
+    }
+
+    #[test]
+    fn it_clamps_out_of_range_thresholds() {
+        let result = detect([[1, 3], [4, 4]], 2.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+
+        let result = detect([[1, 1]], -1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+
+        assert!(supports("t1", [[1, 3]], 2.0));
+    }
+
+    #[test]
+    fn it_scores_zero_totals_as_zero() {
+        assert_eq!(ratio(0, 0), 0.0);
+        assert_eq!(ratio(3, 0), 0.0);
+        assert_eq!(ratio(1, 4), 0.25);
+    }
+
+    #[test]
+    fn it_treats_a_nan_threshold_as_zero() {
+        let result = detect([[1, 1]], f64::NAN);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+
+        assert!(supports("t1", [[1, 1]], f64::NAN));
+    }
+
+    #[test]
+    fn it_detects_scripts_of_overlapping_input() {
+        let result = detect_scripts([[1, 4], [2, 5], [9, 9], [7, 9]], 0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].script, "Cyrl");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[0].score, 1.0);
+        assert_eq!(result[1].script, "Latn");
+        assert_eq!(result[1].count, 5);
+        assert_eq!(result[1].score, 5.0 / 6.0);
+    }
+
+    #[test]
+    fn it_returns_matched_ranges_of_overlapping_input() {
+        let result = detect_detailed([[2, 3], [1, 2], [8, 9], [7, 8]], 1.0);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].inner.tag, "t1");
+        assert_eq!(result[0].inner.count, 3);
+        assert_eq!(result[0].matched_ranges, [[1, 3]]);
+        assert_eq!(result[1].inner.tag, "t3");
+        assert_eq!(result[1].matched_ranges, [[7, 9]]);
+    }
+
+    #[test]
+    fn it_returns_missing_codepoints_of_overlapping_input() {
+        assert_eq!(
+            missing_codepoints("t1", [[3, 3], [1, 2], [2, 2]]),
+            Some(vec![])
+        );
+        assert_eq!(
+            missing_codepoints("t3", [[9, 9], [8, 9]]),
+            Some(vec![[7, 7]])
+        );
+    }
+
+    #[test]
+    fn it_computes_coverage_of_overlapping_input() {
+        let result = coverage("t3", [[9, 9], [8, 9], [8, 8]]).unwrap();
+        assert_eq!(result.matched_count, 2);
+        assert_eq!(result.missing_count, 1);
+        assert_eq!(result.missing_ranges, [[7, 7]]);
+        assert_eq!(result.score, 2.0 / 3.0);
+    }
+
+    #[test]
+    fn it_counts_overlapping_input_once() {
+        let result = detect([[1, 2], [2, 3]], 1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[0].score, 1.0);
+
+        let result = detect([[8, 9], [1, 8], [2, 3]], 0.0);
+        let counts: Vec<_> = result.iter().map(|m| (m.tag, m.count)).collect();
+        assert_eq!(counts, [("t1", 3), ("t2", 3), ("t3", 3), ("t4", 1)]);
+
+        assert!(supports("t4", [[8, 8], [8, 8]], 1.0));
+        assert_eq!(detect_among([[1, 2], [1, 2]], 0.0, &["t1"])[0].count, 2);
+    }
+
+    #[test]
+    fn it_does_not_overflow_on_large_ranges() {
+        let result = detect([[0, u32::MAX]], 1.0);
+        assert_eq!(result.len(), 5);
+        assert_eq!(result[0].count, 3);
+
+        let result = detect([[0, u32::MAX]; 1000], 0.0);
+        assert_eq!(result.len(), 5);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+
+        assert!(supports("t1", [[0, u32::MAX]; 1000], 1.0));
+    }
+
+    #[test]
+    fn it_returns_multiple_languages() {
+        let result = detect([[1, 1], [4, 4]], 0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].name, "test1");
+        assert_eq!(result[1].tag, "t2");
+        assert_eq!(result[1].name, "test2");
+    }
+
+    #[test]
+    fn it_returns_overlapping_languages() {
+        let result = detect([[8, 8]], 0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t4");
+        assert_eq!(result[0].name, "test4");
+        assert_eq!(result[1].tag, "t3");
+        assert_eq!(result[1].name, "test3");
+    }
+
+    #[test]
+    fn it_returns_correct_counts_on_partial_range_matches() {
+        let result = detect([[3, 5]], 0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t2");
+        assert_eq!(result[0].name, "test2");
+        assert_eq!(result[0].count, 2);
+        assert_eq!(result[1].tag, "t1");
+        assert_eq!(result[1].name, "test1");
+        assert_eq!(result[1].count, 1);
+    }
+
+    #[test]
+    fn it_returns_sorted_results() {
+        let result = detect([[1, 1], [4, 6]], 0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t2");
+        assert_eq!(result[1].tag, "t1");
+    }
+
+    #[test]
+    fn it_handles_ranges_correctly() {
+        let result = detect([[12, 20]], 0.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t5");
+    }
+
+    #[test]
+    fn it_returns_the_best_match() {
+        let result = detect_best([[1, 1], [4, 6]], 0.0).unwrap();
+        assert_eq!(result.tag, "t2");
+        assert_eq!(result.count, 3);
+    }
+
+    #[test]
+    fn it_returns_no_best_match_if_threshold_not_met() {
+        assert!(detect_best([[1, 2]], 1.0).is_none());
+        assert!(detect_best([], 0.0).is_none());
+    }
+
+    #[test]
+    fn it_returns_the_same_best_match_as_detect() {
+        let inputs: [&[Range<Codepoint>]; 4] =
+            [&[[8, 8]], &[[1, 1], [4, 4]], &[[3, 5]], &[[1, 9], [16, 16]]];
+
+        for input in inputs {
+            let best = detect_best(input.iter().copied(), 0.0).unwrap();
+            let first = detect(input.iter().copied(), 0.0)
+                .into_iter()
+                .next()
+                .unwrap();
+            assert_eq!(best.tag, first.tag);
+            assert_eq!(best.count, first.count);
+        }
+    }
+
+    #[test]
+    fn it_merges_sorted_overlapping_input_without_allocating() {
+        let input = [[1, 2], [2, 3], [3, 3], [5, 4], [8, 9], [7, 8]];
+        assert!(merge_sorted(input).eq([[1, 3], [7, 9]]));
+
+        let best = detect_best([[1, 2], [2, 3], [3, 3]], 1.0).unwrap();
+        assert_eq!(best.tag, "t1");
+        assert_eq!(best.count, 3);
+
+        let mut result = Vec::new();
+        detect_each([[1, 2], [2, 3], [7, 9], [8, 8]], 1.0, |m| result.push(m));
+        assert_eq!(result, detect([[1, 3], [7, 9]], 1.0));
+    }
+
+    #[test]
+    fn it_ignores_reversed_ranges() {
+        assert_eq!(detect([[5, 1]], 0.0).len(), 0);
+        assert_eq!(detect([[3, 1], [5, 4]], 0.0).len(), 0);
+
+        let result = detect([[1, 1], [6, 4]], 0.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 1);
+
+        assert!(!supports("t1", [[3, 1]], 0.0));
+        assert_eq!(detect_normalized([[3, 1], [1, 2]], 0.0)[0].count, 2);
+    }
+
+    #[test]
+    fn it_detects_checked_input() {
+        let result = detect_checked([[1, 1], [2, 3], [5, 5]], 0.5).unwrap();
+        assert_eq!(result, detect([[1, 3], [5, 5]], 0.5));
+        assert_eq!(detect_checked([], 0.0), Ok(vec![]));
+    }
+
+    #[test]
+    fn it_tries_to_detect() {
+        assert_eq!(try_detect([[1, 3]], 1.0), Ok(detect([[1, 3]], 1.0)));
+        assert_eq!(
+            try_detect([[5, 2]], 0.5),
+            Err(DetectError::ReversedRange { lower: 5, upper: 2 })
+        );
+    }
 
-#[derive(Debug)]
-pub struct Match {
-    /// BCP 47 language tag.
-    pub tag: &'static str,
-    /// English name.
-    pub name: &'static str,
-    /// Name in native script.
-    pub native: &'static str,
-    /// Number of codepoints matched.
-    pub count: u32,
-    /// Score (number of codepoints matched divided by the total).
-    pub score: f64,
-}
+    #[test]
+    fn it_rejects_reversed_ranges() {
+        assert_eq!(
+            detect_checked([[1, 1], [5, 2]], 0.5),
+            Err(DetectError::ReversedRange { lower: 5, upper: 2 })
+        );
+    }
 
-/// Detects language support in a font given a list of Unicode
-/// codepoint ranges.
-///
-/// # Arguments
-///
-/// * `codepoints` - An iterator of codepoint ranges. The iterator
-///   must not contain overlapping ranges and must be sorted in
-///   ascending order.
-/// * `threshold` - The minimum score a language must have to be
-/// returned as a match. Value must be between 0 and 1.
-///
-/// Returns a vector of language matches.
-pub fn detect<T>(codepoints: T, threshold: f64) -> Vec<Match>
-where
-    T: IntoIterator<Item = Range<Codepoint>>,
-{
-    let mut counts = [0; LANGUAGE_COUNT];
+    #[test]
+    fn it_rejects_unsorted_input() {
+        assert_eq!(
+            detect_checked([[4, 6], [1, 2]], 0.5),
+            Err(DetectError::UnsortedInput {
+                previous: [4, 6],
+                range: [1, 2]
+            })
+        );
+    }
 
-    for [input_lower, input_upper] in codepoints {
-        for i in 0..LANGUAGE_COUNT {
-            for [range_lower, range_upper] in RANGES[i] {
-                if input_lower <= *range_upper && *range_lower <= input_upper {
-                    counts[i] += cmp::min(input_upper, *range_upper)
-                        - cmp::max(input_lower, *range_lower)
-                        + 1;
-                }
+    #[test]
+    fn it_rejects_overlapping_ranges() {
+        assert_eq!(
+            detect_checked([[1, 3], [3, 4]], 0.5),
+            Err(DetectError::OverlappingRanges {
+                previous: [1, 3],
+                range: [3, 4]
+            })
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_thresholds() {
+        assert_eq!(
+            detect_checked([[1, 3]], 1.5),
+            Err(DetectError::ThresholdOutOfBounds(1.5))
+        );
+        assert_eq!(
+            detect_checked([[1, 3]], -0.1),
+            Err(DetectError::ThresholdOutOfBounds(-0.1))
+        );
+        assert!(matches!(
+            detect_checked([[1, 3]], f64::NAN),
+            Err(DetectError::ThresholdOutOfBounds(t)) if t.is_nan()
+        ));
+    }
+
+    #[test]
+    fn it_displays_detect_errors() {
+        assert_eq!(
+            DetectError::ReversedRange { lower: 5, upper: 2 }.to_string(),
+            "range [5, 2] is reversed"
+        );
+        assert_eq!(
+            DetectError::ThresholdOutOfBounds(2.0).to_string(),
+            "threshold 2 is not between 0 and 1"
+        );
+    }
+
+    #[test]
+    fn it_detects_normalized_input() {
+        let result = detect_normalized([[2, 5], [1, 3]], 0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[1].tag, "t2");
+        assert_eq!(result[1].count, 2);
+    }
+
+    #[test]
+    fn it_normalizes_ranges() {
+        assert_eq!(normalize_ranges([[2, 5], [1, 3]]), vec![[1, 5]]);
+        assert_eq!(normalize_ranges([[4, 6], [1, 3]]), vec![[1, 6]]);
+        assert_eq!(
+            normalize_ranges([[10, 12], [1, 3], [2, 2], [5, 6]]),
+            vec![[1, 3], [5, 6], [10, 12]]
+        );
+        assert!(normalize_ranges([]).is_empty());
+    }
+
+    #[test]
+    fn it_returns_matches_lazily() {
+        let mut result = detect_iter([[1, 1], [4, 6], [8, 8]], 0.0);
+        assert_eq!(result.next().unwrap().tag, "t2");
+        assert_eq!(result.next().unwrap().tag, "t4");
+        assert_eq!(result.next().unwrap().tag, "t1");
+        assert_eq!(result.next().unwrap().tag, "t3");
+        assert!(result.next().is_none());
+    }
+
+    #[test]
+    fn it_returns_the_same_matches_lazily() {
+        let input = [[1, 1], [4, 4], [8, 9], [16, 16]];
+        let result: Vec<Match> = detect_iter(input, 0.0).collect();
+        let mut sorted = result.clone();
+        sorted.sort();
+        assert_eq!(result, sorted);
+        assert_eq!(
+            detect_iter(input, 0.0).find(|m| m.count == 2).unwrap().tag,
+            "t3"
+        );
+    }
+
+    #[test]
+    fn it_returns_the_top_n_matches() {
+        let result = detect_top_n([[1, 1], [4, 6], [8, 8]], 0.0, 2);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t2");
+        assert_eq!(result[1].tag, "t4");
+    }
+
+    #[test]
+    fn it_returns_no_matches_for_top_zero() {
+        let result = detect_top_n([[1, 9]], 0.0, 0);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn it_returns_all_matches_if_n_is_large() {
+        let input = [[1, 1], [4, 4], [8, 9], [16, 16]];
+        let top = detect_top_n(input, 0.0, 100);
+        let all = detect(input, 0.0);
+        assert_eq!(top.len(), all.len());
+        for (a, b) in top.iter().zip(&all) {
+            assert_eq!(a.tag, b.tag);
+            assert_eq!(a.count, b.count);
+        }
+    }
+
+    #[test]
+    fn it_scores_every_language() {
+        let result = detect_all([[4, 6], [8, 8]]);
+        let tags = result.iter().map(|m| m.tag).collect::<Vec<_>>();
+        assert_eq!(tags, ["t2", "t4", "t3", "t1", "t5"]);
+        assert_eq!(result[2].score, 1.0 / 3.0);
+        assert_eq!(result[3].count, 0);
+        assert_eq!(result[3].score, 0.0);
+    }
+
+    #[test]
+    fn it_scores_every_language_without_codepoints() {
+        let result = detect_all([]);
+        assert_eq!(result.len(), LANGUAGE_COUNT);
+        assert!(result.iter().all(|m| m.score == 0.0));
+    }
+
+    #[test]
+    fn it_supports_a_language() {
+        assert!(supports("t1", [[1, 2]], 0.6));
+        assert!(supports("t1", [[1, 3]], 1.0));
+    }
+
+    #[test]
+    fn it_does_not_support_a_language_if_threshold_not_met() {
+        assert!(!supports("t1", [[1, 2]], 1.0));
+        assert!(!supports("t1", [[4, 6]], 0.0));
+    }
+
+    #[test]
+    fn it_does_not_support_an_unknown_language() {
+        assert!(!supports("xx", [[1, 3]], 0.0));
+    }
+
+    #[test]
+    fn it_supports_a_language_fully() {
+        assert!(supports_fully("t1", [[0, 10]]));
+        assert!(!supports_fully("t1", [[1, 1], [3, 3]]));
+    }
+
+    #[test]
+    fn it_returns_near_misses() {
+        let misses = near_misses([[1, 2], [4, 4], [7, 8]], 0.9, 0.6);
+
+        assert_eq!(
+            misses.iter().map(|m| m.inner.tag).collect::<Vec<_>>(),
+            ["t1", "t3", "t2"]
+        );
+        assert_eq!(
+            misses.iter().map(|m| m.missing_count).collect::<Vec<_>>(),
+            [1, 1, 2]
+        );
+        assert!(near_misses([[1, 3]], 1.0, 0.5).is_empty());
+        assert!(near_misses([[1, 2]], 0.9, 0.1).is_empty());
+    }
+
+    #[test]
+    fn it_partitions_languages_by_the_threshold() {
+        let input = [[1, 3], [4, 4], [8, 8]];
+        let (supported, partial) = partition(input, 0.5);
+
+        assert_eq!(supported, detect(input, 0.5));
+        assert_eq!(
+            partial.iter().map(|m| (m.tag, m.count)).collect::<Vec<_>>(),
+            [("t2", 1), ("t3", 1)]
+        );
+    }
+
+    #[test]
+    fn it_supports_all_languages() {
+        assert!(supports_all(&["t1", "t2"], [[1, 5]], 0.5));
+        assert!(supports_all(&[], [[1, 5]], 1.0));
+        assert!(!supports_all(&["t1", "t2"], [[1, 5]], 0.9));
+        assert!(!supports_all(&["t1", "xx"], [[1, 5]], 0.5));
+    }
 
-                if input_upper <= *range_upper {
-                    break;
+    #[test]
+    fn it_returns_the_unsupported_languages() {
+        let result = unsupported(&["t1", "t2", "xx", "t5"], [[1, 5]], 0.9);
+
+        assert_eq!(result.len(), 3);
+        assert!(matches!(
+            &result[0],
+            Unsupported::BelowThreshold(Match {
+                tag: "t2",
+                count: 2,
+                ..
+            })
+        ));
+        assert_eq!(result[1], Unsupported::Unknown("xx"));
+        assert!(matches!(
+            &result[2],
+            Unsupported::BelowThreshold(Match {
+                tag: "t5",
+                count: 0,
+                ..
+            })
+        ));
+        assert!(unsupported(&["t1", "t2"], [[1, 5]], 0.5).is_empty());
+    }
+
+    #[test]
+    fn it_returns_missing_codepoints() {
+        assert_eq!(
+            missing_codepoints("t1", [[2, 2]]),
+            Some(vec![[1, 1], [3, 3]])
+        );
+        assert_eq!(missing_codepoints("t1", []), Some(vec![[1, 3]]));
+        assert_eq!(
+            missing_codepoints("t1", [[0, 1], [5, 6]]),
+            Some(vec![[2, 3]])
+        );
+    }
+
+    #[test]
+    fn it_returns_no_missing_codepoints_if_fully_covered() {
+        assert_eq!(missing_codepoints("t1", [[0, 10]]), Some(vec![]));
+        assert_eq!(missing_codepoints("t1", [[1, 1], [2, 3]]), Some(vec![]));
+    }
+
+    #[test]
+    fn it_returns_no_missing_codepoints_for_an_unknown_language() {
+        assert_eq!(missing_codepoints("xx", [[1, 3]]), None);
+    }
+
+    #[test]
+    fn it_counts_the_intersection_of_ranges() {
+        let gaps: &[Range<Codepoint>] = &[[2, 4], [6, 6], [8, 12], [20, 30]];
+        let adjacent: &[Range<Codepoint>] = &[[2, 4], [5, 5], [6, 9], [10, 10], [12, 30]];
+
+        for ranges in [gaps, adjacent] {
+            for lower in 0..35 {
+                for upper in lower..35 {
+                    let expected = (lower..=upper)
+                        .filter(|c| ranges.iter().any(|[l, u]| l <= c && c <= u))
+                        .count() as u32;
+                    assert_eq!(count_overlap([lower, upper], ranges), expected);
+                    assert_eq!(intersection([lower, upper], ranges), expected);
                 }
             }
         }
+
+        assert_eq!(intersection([1, 10], &[]), 0);
     }
 
-    let mut result = Vec::new();
+    #[test]
+    fn it_does_not_count_surrogates() {
+        let ranges = [[0xD700, 0xE100]];
+        assert_eq!(intersection([0xD000, 0xE000], &ranges), 0x101);
+        assert_eq!(intersection([0xD800, 0xDFFF], &ranges), 0);
+        assert_eq!(intersection([0xD900, 0xD9FF], &ranges), 0);
+        assert_eq!(intersection([0xDF00, 0xE0FF], &ranges), 0x100);
+        assert_eq!(intersection([0xD7F0, 0xD800], &ranges), 0x10);
+        assert_eq!(intersection([0, u32::MAX], &ranges), 0x100 + 0x101);
+        assert_eq!(intersection([0xD000, 0xE000], &[[1, 3]]), 0);
+        assert_eq!(intersection([0, u32::MAX], &[[1, 3]]), 3);
+    }
 
-    for i in 0..LANGUAGE_COUNT {
-        let score = counts[i] as f64 / TOTALS[i] as f64;
-        if score >= threshold && counts[i] > 0 {
-            result.push(Match {
-                tag: METADATA[i].tag,
-                name: METADATA[i].name,
-                native: METADATA[i].native_name,
-                count: counts[i],
-                score,
-            });
+    /// Generates sorted, non-overlapping ranges starting at `base`.
+    fn sorted_ranges(base: Codepoint) -> impl Strategy<Value = Vec<Range<Codepoint>>> {
+        proptest::collection::vec((0u32..20, 0u32..20), 0..20).prop_map(move |runs| {
+            let mut next = base;
+            runs.into_iter()
+                .map(|(gap, len)| {
+                    let lower = next + gap;
+                    next = lower + len + 1;
+                    [lower, lower + len]
+                })
+                .collect()
+        })
+    }
+
+    /// Generates ranges near the start of the codepoint space, the
+    /// surrogates and the largest codepoint.
+    fn any_sorted_ranges() -> impl Strategy<Value = (Vec<Range<Codepoint>>, Vec<Range<Codepoint>>)>
+    {
+        prop_oneof![Just(0), Just(0xD7F0), Just(0xDFF0), Just(u32::MAX - 1000)]
+            .prop_flat_map(|base| (sorted_ranges(base), sorted_ranges(base)))
+    }
+
+    proptest! {
+        #[test]
+        fn it_counts_the_same_intersection_as_a_set((input, ranges) in any_sorted_ranges()) {
+            for &[lower, upper] in &input {
+                let overlap = (lower..=upper)
+                    .filter(|c| ranges.iter().any(|[l, u]| l <= c && c <= u))
+                    .count() as u32;
+                let expected = (lower..=upper)
+                    .filter(|c| !(SURROGATES[0]..=SURROGATES[1]).contains(c))
+                    .filter(|c| ranges.iter().any(|[l, u]| l <= c && c <= u))
+                    .count() as u32;
+                prop_assert_eq!(count_overlap([lower, upper], &ranges), overlap);
+                prop_assert_eq!(intersection([lower, upper], &ranges), expected);
+            }
+        }
+
+        #[test]
+        fn it_counts_input_ranges_in_any_order(
+            (input, shuffled) in sorted_ranges(0)
+                .prop_flat_map(|input| (Just(input.clone()), Just(input).prop_shuffle()))
+        ) {
+            prop_assert_eq!(counts(shuffled), counts(input));
+        }
+
+        #[test]
+        fn it_never_scores_above_one(input in sorted_ranges(0)) {
+            for m in detect(input, 0.0) {
+                let total = language_by_tag(m.tag).unwrap().total;
+                prop_assert!(m.count <= total);
+                prop_assert!(m.score > 0.0 && m.score <= 1.0);
+            }
         }
     }
 
-    result.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap().reverse());
+    #[test]
+    fn it_returns_the_coverage_of_a_language() {
+        let result = coverage("t1", [[2, 2]]).unwrap();
+        assert_eq!(result.matched_count, 1);
+        assert_eq!(result.missing_count, 2);
+        assert_eq!(result.total, 3);
+        assert_eq!(result.score, 1.0 / 3.0);
+        assert_eq!(result.missing_ranges, vec![[1, 1], [3, 3]]);
+    }
+
+    #[test]
+    fn it_returns_full_coverage() {
+        let result = coverage("t2", [[0, 10]]).unwrap();
+        assert_eq!(result.matched_count, 3);
+        assert_eq!(result.missing_count, 0);
+        assert_eq!(result.score, 1.0);
+        assert!(result.missing_ranges.is_empty());
+    }
 
-    result
-}
+    #[test]
+    fn it_checks_whether_coverage_is_complete() {
+        let result = coverage("t2", [[0, 10]]).unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.percent_complete(), 100.0);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let result = coverage("t1", [[1, 2]]).unwrap();
+        assert!(!result.is_complete());
+        assert_eq!(result.percent_complete(), result.score * 100.0);
+
+        let result = Coverage {
+            score: 1.0 - 1e-12,
+            ..result
+        };
+        assert!(result.is_complete());
+    }
 
     #[test]
-    fn it_returns_an_empty_array() {
-        let result = detect([], 0.5);
-        assert_eq!(result.len(), 0);
+    fn it_returns_no_coverage_for_an_unknown_language() {
+        assert!(coverage("xx", [[1, 3]]).is_none());
     }
 
     #[test]
-    fn it_takes_a_vector() {
-        let codepoints = vec![[1, 3]];
+    fn it_compares_two_fonts() {
+        let result = diff([[1, 3], [7, 9]], [[4, 9]], 1.0);
+        let tags = |matches: &[Match]| matches.iter().map(|m| m.tag).collect::<Vec<_>>();
+        assert_eq!(tags(&result.only_a), ["t1"]);
+        assert_eq!(tags(&result.only_b), ["t2"]);
+        assert_eq!(tags(&result.both), ["t3", "t4"]);
+    }
 
-        let result = detect(codepoints, 1.0);
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].tag, "t1");
-        assert_eq!(result[0].name, "test1");
+    #[test]
+    fn it_compares_identical_fonts() {
+        let result = diff([[1, 9]], [[1, 9]], 0.0);
+        assert!(result.only_a.is_empty());
+        assert!(result.only_b.is_empty());
+        assert_eq!(result.both, detect([[1, 9]], 0.0));
     }
 
     #[test]
-    fn it_takes_an_array() {
-        let codepoints = [[1, 3]];
+    fn it_returns_unmatched_codepoints() {
+        assert_eq!(
+            unmatched_codepoints([[0, 12], [16, 20]]),
+            vec![[0, 0], [10, 12], [17, 20]]
+        );
+        assert!(unmatched_codepoints([[8, 9], [1, 5]]).is_empty());
+        assert!(unmatched_codepoints([]).is_empty());
+    }
 
-        let result = detect(codepoints, 1.0);
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].tag, "t1");
-        assert_eq!(result[0].name, "test1");
+    #[test]
+    fn it_explains_coverage() {
+        let result = explain_coverage([[1, 9], [20, 21]], 0.0);
+        let tags = result.matches.iter().map(|m| m.tag).collect::<Vec<_>>();
+        assert_eq!(tags, ["t1", "t2", "t3"]);
+        assert_eq!(result.unexplained, vec![[20, 21]]);
     }
 
     #[test]
-    fn it_returns_an_empty_array_with_an_invalid_codepoint() {
-        let result = detect([[256, 256]], 0.5);
-        assert_eq!(result.len(), 0);
+    fn it_only_explains_with_languages_meeting_the_threshold() {
+        let result = explain_coverage([[1, 3], [7, 7]], 0.5);
+        let tags = result.matches.iter().map(|m| m.tag).collect::<Vec<_>>();
+        assert_eq!(tags, ["t1"]);
+        assert_eq!(result.unexplained, vec![[7, 7]]);
+        assert!(explain_coverage([], 0.0).matches.is_empty());
     }
 
     #[test]
-    fn it_returns_the_test_language() {
-        let result = detect([[1, 1]], 0.0);
+    fn it_detects_a_string() {
+        let result = detect_str("\u{1}\u{2}\u{3}", 1.0);
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].tag, "t1");
-        assert_eq!(result[0].name, "test1")
+        assert_eq!(result[0].count, 3);
     }
 
     #[test]
-    fn it_does_not_return_if_threshold_not_met() {
-        let result = detect([[1, 2]], 1.0);
-        assert_eq!(result.len(), 0);
+    fn it_counts_repeated_characters_once() {
+        let result = detect_str("\u{2}\u{1}\u{2}\u{1}\u{5}", 0.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].tag, "t1");
+        assert_eq!(result[0].count, 2);
+        assert_eq!(result[1].tag, "t2");
+        assert_eq!(result[1].count, 1);
     }
 
     #[test]
-    fn it_returns_if_threshold_is_met() {
-        let result = detect([[1, 3]], 1.0);
+    fn it_detects_unsorted_codepoints() {
+        let result = detect_codepoints([3, 1, 2], 1.0);
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].tag, "t1");
-        assert_eq!(result[0].name, "test1");
+        assert_eq!(result[0].count, 3);
     }
 
     #[test]
-    fn it_returns_if_threshold_is_partially_met() {
-        let result = detect([[1, 2]], 0.6);
+    fn it_detects_duplicate_codepoints() {
+        let result = detect_codepoints([1, 1, 2, 2, 3, 3], 1.0);
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].tag, "t1");
-        assert_eq!(result[0].name, "test1");
+        assert_eq!(result[0].count, 3);
     }
 
     #[test]
-    fn it_returns_multiple_languages() {
-        let result = detect([[1, 1], [4, 4]], 0.0);
+    fn it_detects_codepoints_with_gaps() {
+        let result = detect_codepoints([16, 1, 3], 0.0);
         assert_eq!(result.len(), 2);
-        assert_eq!(result[0].tag, "t1");
-        assert_eq!(result[0].name, "test1");
-        assert_eq!(result[1].tag, "t2");
-        assert_eq!(result[1].name, "test2");
+        assert_eq!(result[0].tag, "t5");
+        assert_eq!(result[0].count, 1);
+        assert_eq!(result[1].tag, "t1");
+        assert_eq!(result[1].count, 2);
     }
 
     #[test]
-    fn it_returns_overlapping_languages() {
-        let result = detect([[8, 8]], 0.0);
+    fn it_detects_scripts() {
+        let result = detect_scripts([[1, 3], [8, 8]], 0.0);
         assert_eq!(result.len(), 2);
-        assert_eq!(result[0].tag, "t4");
-        assert_eq!(result[0].name, "test4");
-        assert_eq!(result[1].tag, "t3");
-        assert_eq!(result[1].name, "test3");
+        assert_eq!(result[0].script, "Latn");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[0].total, 6);
+        assert_eq!(result[0].score, 0.5);
+        assert_eq!(result[1].script, "Cyrl");
+        assert_eq!(result[1].count, 1);
+        assert_eq!(result[1].total, 3);
     }
 
     #[test]
-    fn it_returns_correct_counts_on_partial_range_matches() {
-        let result = detect([[3, 5]], 0.0);
+    fn it_counts_shared_script_codepoints_once() {
+        let result = detect_scripts([[7, 9], [16, 16]], 1.0);
         assert_eq!(result.len(), 2);
-        assert_eq!(result[0].tag, "t2");
-        assert_eq!(result[0].name, "test2");
-        assert_eq!(result[0].count, 2);
-        assert_eq!(result[1].tag, "t1");
-        assert_eq!(result[1].name, "test1");
-        assert_eq!(result[1].count, 1);
+        assert_eq!(result[0].script, "Cyrl");
+        assert_eq!(result[0].count, 3);
+        assert_eq!(result[1].script, "Grek");
+        assert!(detect_scripts([[1, 1]], 0.5).is_empty());
     }
 
     #[test]
-    fn it_returns_sorted_results() {
-        let result = detect([[1, 1], [4, 6]], 0.0);
+    fn it_coalesces_codepoints() {
+        assert_eq!(coalesce([67, 65, 66, 66, 70]), vec![[65, 67], [70, 70]]);
+        assert_eq!(coalesce([]), Vec::<Range<Codepoint>>::new());
+        assert_eq!(
+            coalesce([u32::MAX, u32::MAX - 1, 0]),
+            vec![[0, 0], [u32::MAX - 1, u32::MAX]]
+        );
+    }
+
+    #[test]
+    fn it_calls_a_function_for_each_match() {
+        let input = [[1, 1], [4, 6], [8, 8]];
+        let mut result = Vec::new();
+
+        detect_each(input, 0.3, |m| result.push(m));
+        assert_eq!(result, detect(input, 0.3));
+
+        detect_each(input, 1.0, |m| assert_eq!(m.score, 1.0));
+    }
+
+    #[test]
+    fn it_returns_the_score_in_percent() {
+        let result = detect([[1, 3], [4, 5]], 0.0);
+        assert_eq!(result[0].percent(), 100.0);
+        assert_eq!(result[1].percent(), result[1].score * 100.0);
+    }
+
+    #[test]
+    fn it_detects_with_a_threshold_in_percent() {
+        let input = [[1, 3], [4, 5]];
+        assert_eq!(detect_pct(input, 67), Ok(detect(input, 0.67)));
+        assert_eq!(detect_pct(input, 100), Ok(detect(input, 1.0)));
+        assert_eq!(
+            detect_pct(input, 101),
+            Err(DetectError::ThresholdOutOfBounds(1.01))
+        );
+    }
+
+    #[test]
+    fn it_detects_the_languages_of_a_font_family() {
+        let regular = vec![[1, 2], [4, 5]];
+        let italic = vec![[2, 3], [5, 6]];
+
+        let result = union_coverage([regular, italic], 1.0);
+        assert_eq!(
+            result.iter().map(|m| m.tag).collect::<Vec<_>>(),
+            ["t1", "t2"]
+        );
+        assert_eq!(result[0].count, 3);
+        assert!(union_coverage([], 0.0).is_empty());
+    }
+
+    #[test]
+    fn it_merges_unsorted_font_ranges_in_a_family() {
+        let regular = vec![[4, 5], [2, 3], [1, 2]];
+        let italic = vec![[6, 6], [5, 6]];
+
+        let result = union_coverage([regular, italic], 1.0);
+        assert_eq!(
+            result.iter().map(|m| (m.tag, m.count)).collect::<Vec<_>>(),
+            [("t1", 3), ("t2", 3)]
+        );
+    }
+
+    #[test]
+    fn it_attributes_coverage_to_fonts() {
+        let fonts = [
+            ("Regular", vec![[1, 2], [4, 6]]),
+            ("Italic", vec![[1, 1], [3, 6]]),
+        ];
+
+        let result = attribute_coverage(&fonts, 1.0);
         assert_eq!(result.len(), 2);
-        assert_eq!(result[0].tag, "t2");
-        assert_eq!(result[1].tag, "t1");
+        assert_eq!(result[0].0.tag, "t1");
+        assert!(result[0].1.is_empty());
+        assert_eq!(result[1].0.tag, "t2");
+        assert_eq!(result[1].1, ["Regular", "Italic"]);
     }
 
     #[test]
-    fn it_handles_ranges_correctly() {
-        let result = detect([[12, 20]], 0.0);
+    fn it_excludes_languages() {
+        let result = detect_excluding([[1, 9]], 0.0, &["t1", "t3", "xx"]);
+        assert_eq!(
+            result.iter().map(|m| m.tag).collect::<Vec<_>>(),
+            ["t2", "t4"]
+        );
+        assert_eq!(detect_excluding([[1, 9]], 0.0, &[]), detect([[1, 9]], 0.0));
+    }
+
+    #[test]
+    fn it_detects_among_languages() {
+        let result = detect_among([[1, 2], [4, 9]], 0.0, &["t3", "t1", "xx", "t4", "t1"]);
+        assert_eq!(
+            result.iter().map(|m| m.tag).collect::<Vec<_>>(),
+            ["t3", "t4", "t1"]
+        );
+        assert_eq!(result[2].count, 2);
+        assert!(detect_among([[1, 3]], 0.0, &["t2"]).is_empty());
+    }
+
+    #[test]
+    fn it_detects_into_a_buffer() {
+        let mut out = detect([[1, 3]], 0.0);
+
+        detect_into([[4, 6], [8, 8]], 0.0, &mut out);
+        assert_eq!(out, detect([[4, 6], [8, 8]], 0.0));
+
+        let capacity = out.capacity();
+        detect_into([], 0.0, &mut out);
+        assert!(out.is_empty());
+        assert_eq!(out.capacity(), capacity);
+    }
+
+    #[test]
+    fn it_detects_char_ranges() {
+        assert_eq!(
+            detect_chars([['\u{1}', '\u{3}'], ['\u{8}', '\u{8}']], 0.5),
+            detect([[1, 3], [8, 8]], 0.5)
+        );
+    }
+
+    #[test]
+    fn it_converts_char_ranges() {
+        assert_eq!(char_range('a'..='z'), [97, 122]);
+        assert_eq!(char_range('\u{D7FF}'..='\u{E000}'), [0xD7FF, 0xE000]);
+        assert_eq!(
+            detect([char_range('\u{1}'..='\u{3}')], 1.0),
+            detect([[1, 3]], 1.0)
+        );
+        assert!(detect([char_range('\u{3}'..='\u{1}')], 0.0).is_empty());
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn it_normalizes_text_before_detecting() {
+        let result = detect_str_nfc("\u{1}\u{2}\u{3}e\u{301}", 1.0);
+        assert_eq!(result, detect_str("\u{1}\u{2}\u{3}", 1.0));
+        assert_eq!(detect_str_nfc("e\u{301}", 0.0), detect_str("é", 0.0));
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn it_counts_the_first_codepoint_of_each_grapheme() {
+        let result = detect_graphemes("\u{6}e\u{4}g\u{301}", 0.0);
         assert_eq!(result.len(), 1);
-        assert_eq!(result[0].tag, "t5");
+        assert_eq!(result[0].tag, "t2");
+        assert_eq!(result[0].count, 2);
+        assert!(detect_graphemes("a\u{301}", 0.0).is_empty());
+    }
+
+    #[test]
+    fn it_returns_an_empty_array_for_an_empty_string() {
+        let result = detect_str("", 0.0);
+        assert_eq!(result.len(), 0);
     }
 }
@@ -0,0 +1,152 @@
+//! Set operations on lists of codepoint ranges.
+//!
+//! All functions take ranges sorted in ascending order that do not
+//! overlap, such as the ranges returned by `coalesce`, and return
+//! ranges in the same form with adjacent ranges merged.
+
+use alloc::vec::Vec;
+use core::cmp;
+
+use crate::{Codepoint, Range};
+
+/// Returns the codepoints in either `ranges` or `other`.
+pub fn union(ranges: &[Range<Codepoint>], other: &[Range<Codepoint>]) -> Vec<Range<Codepoint>> {
+    let mut result = Vec::with_capacity(ranges.len() + other.len());
+    let (mut i, mut j) = (0, 0);
+
+    while i < ranges.len() || j < other.len() {
+        let range = if j == other.len() || (i < ranges.len() && ranges[i][0] <= other[j][0]) {
+            i += 1;
+            ranges[i - 1]
+        } else {
+            j += 1;
+            other[j - 1]
+        };
+
+        push_range(&mut result, range);
+    }
+
+    result
+}
+
+/// Returns the codepoints in both `ranges` and `other`.
+pub fn intersect(ranges: &[Range<Codepoint>], other: &[Range<Codepoint>]) -> Vec<Range<Codepoint>> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < ranges.len() && j < other.len() {
+        let lower = cmp::max(ranges[i][0], other[j][0]);
+        let upper = cmp::min(ranges[i][1], other[j][1]);
+
+        if lower <= upper {
+            push_range(&mut result, [lower, upper]);
+        }
+
+        if ranges[i][1] < other[j][1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    result
+}
+
+/// Returns the codepoints in `ranges` that are not in `other`.
+pub fn difference(
+    ranges: &[Range<Codepoint>],
+    other: &[Range<Codepoint>],
+) -> Vec<Range<Codepoint>> {
+    let mut result = Vec::new();
+    let mut start = 0;
+
+    for &[lower, upper] in ranges {
+        while start < other.len() && other[start][1] < lower {
+            start += 1;
+        }
+
+        let mut lower = lower;
+        let mut covered = false;
+
+        for &[other_lower, other_upper] in other[start..].iter().take_while(|r| r[0] <= upper) {
+            if other_lower > lower {
+                push_range(&mut result, [lower, other_lower - 1]);
+            }
+
+            if other_upper >= upper {
+                covered = true;
+                break;
+            }
+
+            lower = cmp::max(lower, other_upper + 1);
+        }
+
+        if !covered {
+            push_range(&mut result, [lower, upper]);
+        }
+    }
+
+    result
+}
+
+/// Appends a range to a sorted list of ranges, merging it with the
+/// last range if they overlap or are adjacent.
+pub(crate) fn push_range(ranges: &mut Vec<Range<Codepoint>>, [lower, upper]: Range<Codepoint>) {
+    match ranges.last_mut() {
+        Some([_, last_upper]) if lower <= last_upper.saturating_add(1) => {
+            *last_upper = cmp::max(*last_upper, upper);
+        }
+        _ => ranges.push([lower, upper]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn it_returns_the_union_of_ranges() {
+        assert_eq!(
+            union(&[[1, 3], [10, 12], [20, 20]], &[[4, 5], [11, 15], [30, 40]]),
+            vec![[1, 5], [10, 15], [20, 20], [30, 40]]
+        );
+        assert_eq!(union(&[[1, 3]], &[]), vec![[1, 3]]);
+        assert_eq!(union(&[], &[[1, 3]]), vec![[1, 3]]);
+        assert!(union(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn it_returns_the_intersection_of_ranges() {
+        assert_eq!(
+            intersect(&[[1, 10], [20, 30]], &[[2, 3], [5, 5], [9, 21], [30, 40]]),
+            vec![[2, 3], [5, 5], [9, 10], [20, 21], [30, 30]]
+        );
+        assert!(intersect(&[[1, 3]], &[[4, 6]]).is_empty());
+        assert!(intersect(&[], &[[1, 3]]).is_empty());
+        assert!(intersect(&[[1, 3]], &[]).is_empty());
+    }
+
+    #[test]
+    fn it_returns_the_difference_of_ranges() {
+        assert_eq!(
+            difference(&[[1, 10], [20, 30]], &[[2, 3], [5, 5], [9, 21], [30, 40]]),
+            vec![[1, 1], [4, 4], [6, 8], [22, 29]]
+        );
+        assert_eq!(difference(&[[1, 3], [4, 6]], &[]), vec![[1, 6]]);
+        assert_eq!(difference(&[[1, 3]], &[[4, 6]]), vec![[1, 3]]);
+        assert!(difference(&[], &[[1, 3]]).is_empty());
+        assert!(difference(&[[1, 3]], &[[0, u32::MAX]]).is_empty());
+    }
+
+    #[test]
+    fn it_handles_the_largest_codepoint() {
+        let all = [[0, u32::MAX]];
+        assert_eq!(union(&all, &[[5, 5]]), vec![[0, u32::MAX]]);
+        assert_eq!(
+            intersect(&all, &[[u32::MAX, u32::MAX]]),
+            vec![[u32::MAX, u32::MAX]]
+        );
+        assert_eq!(difference(&all, &[[1, u32::MAX]]), vec![[0, 0]]);
+    }
+}
@@ -0,0 +1,147 @@
+//! Detection from UTF-8 text read from an `io::Read` source.
+
+use std::io::{self, Read};
+use std::str;
+use std::vec;
+use std::vec::Vec;
+
+use crate::{detect, push_range, Codepoint, Match, Range};
+
+/// The number of Unicode codepoints.
+const CODEPOINT_COUNT: usize = 0x110000;
+
+/// Detects language support given UTF-8 text read from a reader.
+///
+/// The text is decoded in chunks as it is read, and only the set of
+/// distinct characters seen so far is kept in memory, so the text can
+/// be much larger than the available memory. Each distinct character
+/// is counted once, the same as in `detect_str`.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the text.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a vector of language matches, sorted the same way as in
+/// `detect`. Returns an error with the kind `InvalidData` if the text
+/// is not valid UTF-8, or any error returned by the reader other than
+/// `Interrupted`.
+pub fn detect_reader<R: Read>(mut reader: R, threshold: f64) -> io::Result<Vec<Match>> {
+    let mut seen = vec![0u64; CODEPOINT_COUNT / 64];
+    let mut buffer = vec![0u8; 8192];
+    // The number of bytes at the start of the buffer left over from the
+    // previous read, which are the start of an incomplete character.
+    let mut pending = 0;
+
+    loop {
+        let read = match reader.read(&mut buffer[pending..]) {
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+
+        if read == 0 {
+            if pending > 0 {
+                return Err(invalid_utf8());
+            }
+            break;
+        }
+
+        let len = pending + read;
+        let valid = match str::from_utf8(&buffer[..len]) {
+            Ok(text) => text,
+            Err(error) if error.error_len().is_none() => {
+                str::from_utf8(&buffer[..error.valid_up_to()]).unwrap()
+            }
+            Err(_) => return Err(invalid_utf8()),
+        };
+
+        for c in valid.chars() {
+            let codepoint = c as usize;
+            seen[codepoint / 64] |= 1 << (codepoint % 64);
+        }
+
+        let valid_len = valid.len();
+        buffer.copy_within(valid_len..len, 0);
+        pending = len - valid_len;
+    }
+
+    Ok(detect(ranges(&seen), threshold))
+}
+
+/// Returns the codepoints set in a bitset as sorted, coalesced ranges.
+fn ranges(seen: &[u64]) -> Vec<Range<Codepoint>> {
+    let mut ranges = Vec::new();
+
+    for (i, &word) in seen.iter().enumerate() {
+        let mut word = word;
+
+        while word != 0 {
+            let codepoint = (i * 64) as Codepoint + word.trailing_zeros();
+            push_range(&mut ranges, [codepoint, codepoint]);
+            word &= word - 1;
+        }
+    }
+
+    ranges
+}
+
+/// Returns the error for text that is not valid UTF-8.
+fn invalid_utf8() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect_str;
+
+    /// A reader returning at most one byte per read.
+    struct Bytewise<'a>(&'a [u8]);
+
+    impl Read for Bytewise<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn it_detects_text_from_a_reader() {
+        let text = "\u{1}\u{2}\u{3}\u{8}é\u{10}";
+
+        assert_eq!(
+            detect_reader(text.as_bytes(), 0.0).unwrap(),
+            detect_str(text, 0.0)
+        );
+        assert_eq!(
+            detect_reader(Bytewise(text.as_bytes()), 0.0).unwrap(),
+            detect_str(text, 0.0)
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_utf8() {
+        let error = detect_reader(&[1, 0xFF, 2][..], 0.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let error = detect_reader(&"é".as_bytes()[..1], 0.0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn it_returns_the_ranges_of_a_bitset() {
+        let mut seen = vec![0u64; 4];
+        seen[0] = 0b1110;
+        seen[1] = 1;
+        seen[3] = 1 << 63;
+
+        assert_eq!(ranges(&seen), [[1, 3], [64, 64], [255, 255]]);
+    }
+}
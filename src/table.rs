@@ -0,0 +1,95 @@
+//! Formatting of matches as a table.
+
+use core::fmt::{self, Write};
+
+use crate::Match;
+
+/// The widths of the tag, name and native name columns, in characters.
+const TAG_WIDTH: usize = 12;
+const NAME_WIDTH: usize = 24;
+const NATIVE_WIDTH: usize = 20;
+
+/// Writes matches as a table with aligned columns for the tag, English
+/// name, native name, score in percent and matched codepoint count.
+///
+/// The first row is a header. Names longer than their column are
+/// truncated and end in `…`. Widths are counted in characters, so
+/// columns of names with wide or combining characters may not line up
+/// in a terminal.
+///
+/// # Arguments
+///
+/// * `w` - The writer to write the table to.
+/// * `matches` - The matches to write, one per row, in order.
+pub fn write_report<W: Write>(w: &mut W, matches: &[Match]) -> fmt::Result {
+    writeln!(
+        w,
+        "{:<TAG_WIDTH$} {:<NAME_WIDTH$} {:<NATIVE_WIDTH$} {:>7} {:>7}",
+        "Tag", "Name", "Native name", "Score", "Count"
+    )?;
+
+    for m in matches {
+        writeln!(
+            w,
+            "{:<TAG_WIDTH$} {:<NAME_WIDTH$} {:<NATIVE_WIDTH$} {:>6.1}% {:>7}",
+            Truncated(m.tag, TAG_WIDTH),
+            Truncated(m.name, NAME_WIDTH),
+            Truncated(m.native, NATIVE_WIDTH),
+            m.percent(),
+            m.count
+        )?;
+    }
+
+    Ok(())
+}
+
+/// A string truncated to a number of characters when displayed.
+struct Truncated<'a>(&'a str, usize);
+
+impl fmt::Display for Truncated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Truncated(s, width) = *self;
+
+        match s.char_indices().nth(width.saturating_sub(1)) {
+            Some((end, _)) if s.chars().count() > width => {
+                f.write_str(&s[..end])?;
+                f.write_char('…')
+            }
+            _ => f.pad(s),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::detect;
+    use alloc::{format, string::String, vec::Vec};
+
+    #[test]
+    fn it_writes_a_table_of_matches() {
+        let mut table = String::new();
+        write_report(&mut table, &detect([[1, 3], [4, 5]], 0.5)).unwrap();
+
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Tag          Name"));
+        assert_eq!(
+            lines[1],
+            "t1           test1                    ntest1                100.0%       3"
+        );
+        assert!(lines[2].ends_with(" 66.7%       2"));
+        assert!(lines
+            .iter()
+            .all(|line| line.chars().count() == lines[0].chars().count()));
+    }
+
+    #[test]
+    fn it_truncates_long_names() {
+        let long = "Sehr langer Name mit vielen Wörtern";
+
+        assert_eq!(format!("{:<10}|", Truncated(long, 10)), "Sehr lang…|");
+        assert_eq!(format!("{:<10}|", Truncated("Wörter", 10)), "Wörter    |");
+        assert_eq!(format!("{:<6}|", Truncated("Wörter", 6)), "Wörter|");
+    }
+}
@@ -0,0 +1,40 @@
+use alloc::string::String;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::detect_codepoints;
+
+/// Detects language support given a list of individual Unicode
+/// codepoints and returns the matches as a JSON array.
+///
+/// # Arguments
+///
+/// * `codepoints` - A list of codepoints. The list may be unsorted
+///   and contain duplicates.
+/// * `threshold` - The minimum score a language must have to be
+///   returned as a match. Value must be between 0 and 1.
+///
+/// Returns a JSON array of language matches.
+#[wasm_bindgen(js_name = detectJson)]
+pub fn detect_json(codepoints: &[u32], threshold: f64) -> String {
+    let matches = detect_codepoints(codepoints.iter().copied(), threshold);
+
+    serde_json::to_string(&matches).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_returns_an_empty_json_array() {
+        assert_eq!(detect_json(&[], 0.0), "[]");
+    }
+
+    #[test]
+    fn it_returns_json_matches() {
+        assert_eq!(
+            detect_json(&[3, 1, 2, 2, 4], 1.0),
+            r#"[{"tag":"t1","name":"test1","native":"ntest1","script":"Latn","count":3,"total":3,"score":1.0,"speakers":100}]"#
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Tests for the parsing of the language data in the build script.
+
+#[allow(dead_code)]
+#[path = "../build/languages.rs"]
+mod languages;
+
+use std::path::Path;
+
+use languages::{
+    check_duplicate_tags, coalesce, intern, language_tag, parse_language, remove_empty, subtract,
+    total, Range,
+};
+
+fn fixture(path: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(path)
+}
+
+#[test]
+fn it_rejects_duplicate_tags() {
+    let languages = [
+        parse_language(fixture("duplicate-tags/en")).unwrap(),
+        parse_language(fixture("duplicate-tags/en.json")).unwrap(),
+    ];
+
+    let errors = check_duplicate_tags(&languages).unwrap_err();
+    let [error] = errors.as_slice() else {
+        panic!("{errors:?}");
+    };
+    assert!(error.starts_with("duplicate language tag en in "));
+    assert!(error.contains("duplicate-tags/en, "));
+    assert!(error.ends_with("duplicate-tags/en.json"));
+}
+
+#[test]
+fn it_accepts_distinct_tags() {
+    let languages = [parse_language(fixture("duplicate-tags/en")).unwrap()];
+
+    assert!(check_duplicate_tags(&languages).is_ok());
+}
+
+#[test]
+fn it_names_the_file_of_a_parse_error() {
+    let error = parse_language(fixture("malformed/xx.json")).unwrap_err();
+    assert!(error.contains("malformed/xx.json: "), "{error}");
+    assert!(error.contains("line 3 column"), "{error}");
+
+    let error = parse_language(fixture("malformed/xx")).unwrap_err();
+    assert!(error.contains("malformed/xx: "), "{error}");
+    assert!(error.contains("invalid range \"a..b\""), "{error}");
+}
+
+#[test]
+fn it_parses_range_strings() {
+    let range = |s: &str| serde_json::from_str::<Range>(&format!("{s:?}")).unwrap();
+
+    assert_eq!(range("65..90"), Range(65, 90));
+    assert_eq!(range("65..=90"), Range(65, 90));
+    assert_eq!(range("65...90"), Range(65, 89));
+    assert_eq!(range(" 65 .. 90 "), Range(65, 90));
+    assert_eq!(range("65"), Range(65, 65));
+}
+
+#[test]
+fn it_rejects_malformed_range_strings() {
+    let error = |s: &str| {
+        serde_json::from_str::<Range>(&format!("{s:?}"))
+            .unwrap_err()
+            .to_string()
+    };
+
+    for (s, message) in [
+        ("", r#"invalid range "": "" is not a codepoint"#),
+        ("..", r#"invalid range "..": "" is not a codepoint"#),
+        ("65..", r#"invalid range "65..": "" is not a codepoint"#),
+        ("..=90", r#"invalid range "..=90": "" is not a codepoint"#),
+        (
+            "65-90",
+            r#"invalid range "65-90": "65-90" is not a codepoint"#,
+        ),
+        ("a..b", r#"invalid range "a..b": "a" is not a codepoint"#),
+        (
+            "1..2..3",
+            r#"invalid range "1..2..3": expected exactly two codepoints"#,
+        ),
+        (
+            "1..=2..3",
+            r#"invalid range "1..=2..3": expected exactly two codepoints"#,
+        ),
+    ] {
+        let error = error(s);
+        assert!(error.starts_with(message), "{error}");
+    }
+}
+
+#[test]
+fn it_excludes_the_upper_codepoint_of_exclusive_ranges() {
+    let language = parse_language(fixture("range-syntax/xx")).unwrap();
+    assert_eq!(
+        language.codepoints,
+        [Range(65, 70), Range(72, 74), Range(80, 82)]
+    );
+    assert_eq!(total(&language.codepoints), 12);
+}
+
+#[test]
+fn it_rejects_reversed_ranges() {
+    let error = parse_language(fixture("reversed-range/xx")).unwrap_err();
+    assert!(error.contains("reversed-range/xx: "), "{error}");
+    assert!(error.contains("reversed range 90..65"), "{error}");
+}
+
+#[test]
+fn it_rejects_ranges_above_the_largest_codepoint() {
+    let error = parse_language(fixture("out-of-range/xx")).unwrap_err();
+    assert!(error.contains("out-of-range/xx: "), "{error}");
+    assert!(
+        error.contains("range 65..1114112 is above U+10FFFF"),
+        "{error}"
+    );
+}
+
+#[test]
+fn it_rejects_invalid_tags() {
+    let error = parse_language(fixture("invalid-tag/x_y")).unwrap_err();
+    assert!(error.contains("invalid-tag/x_y: "), "{error}");
+    assert!(error.contains("invalid language tag \"x_y\""), "{error}");
+}
+
+#[test]
+fn it_takes_the_tag_from_the_file_name() {
+    assert_eq!(language_tag(Path::new("data/sr-Latn")), Ok("sr-Latn"));
+    assert_eq!(language_tag(Path::new("data/en.json")), Ok("en"));
+    assert_eq!(language_tag(Path::new("data/de.yml")), Ok("de"));
+    assert!(language_tag(Path::new("data/README.md")).is_err());
+    assert!(language_tag(Path::new("data/.DS_Store")).is_err());
+}
+
+#[test]
+fn it_takes_the_tag_from_the_file_stem() {
+    let language = parse_language(fixture("nested/Latn/xx.yaml")).unwrap();
+    assert_eq!(language.tag.as_deref(), Some("xx"));
+}
+
+#[test]
+fn it_interns_identical_ranges() {
+    let ranges = [
+        vec![Range(65, 90)],
+        vec![Range(97, 122)],
+        vec![Range(65, 90)],
+    ];
+
+    let (sets, indices) = intern(&ranges);
+    assert_eq!(sets, [[Range(65, 90)], [Range(97, 122)]]);
+    assert_eq!(indices, [0, 1, 0]);
+
+    for (ranges, i) in ranges.iter().zip(indices) {
+        assert_eq!(sets[i], ranges.as_slice());
+    }
+}
+
+#[test]
+fn it_removes_languages_without_codepoints() {
+    let languages = vec![
+        parse_language(fixture("empty/xx")).unwrap(),
+        parse_language(fixture("duplicate-tags/en")).unwrap(),
+    ];
+
+    let (languages, warnings) = remove_empty(languages);
+    assert_eq!(languages.len(), 1);
+    assert_eq!(languages[0].tag.as_deref(), Some("en"));
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("empty/xx: language xx has no codepoints"));
+}
+
+#[test]
+fn it_names_a_missing_file() {
+    let error = parse_language(fixture("missing/xx")).unwrap_err();
+    assert!(error.contains("missing/xx: "), "{error}");
+}
+
+#[test]
+fn it_counts_the_supported_languages() {
+    assert_eq!(
+        unicode_language::languages().count(),
+        unicode_language::SUPPORTED_LANGUAGE_COUNT
+    );
+}
+
+#[test]
+fn it_defaults_the_native_name_to_the_anglicized_name() {
+    let language = parse_language(fixture("no-native-name/xx")).unwrap();
+    assert_eq!(language.native_name.as_deref(), Some("Nameless"));
+
+    let language = parse_language(fixture("duplicate-tags/en")).unwrap();
+    assert_eq!(language.native_name.as_deref(), Some("English"));
+}
+
+#[test]
+fn it_merges_overlapping_and_adjacent_ranges() {
+    let ranges = vec![
+        Range(1, 3),
+        Range(2, 5),
+        Range(6, 6),
+        Range(8, 9),
+        Range(10, 10),
+    ];
+    let merged = coalesce(ranges);
+    assert_eq!(merged, [Range(1, 6), Range(8, 10)]);
+    assert_eq!(total(&merged), 9);
+}
+
+#[test]
+fn it_keeps_the_total_of_nested_ranges() {
+    let merged = coalesce(vec![Range(1, 10), Range(2, 4), Range(4, 12), Range(12, 12)]);
+    assert_eq!(merged, [Range(1, 12)]);
+
+    let merged = coalesce(vec![Range(0, u32::MAX), Range(5, u32::MAX)]);
+    assert_eq!(merged, [Range(0, u32::MAX)]);
+}
+
+#[test]
+fn it_merges_the_ranges_of_a_language() {
+    let language = parse_language(fixture("adjacent-ranges/xx")).unwrap();
+    assert_eq!(language.codepoints, [Range(65, 70), Range(72, 72)]);
+}
+
+#[test]
+fn it_separates_auxiliary_from_main_codepoints() {
+    let language = parse_language(fixture("auxiliary/xx")).unwrap();
+    assert_eq!(language.codepoints, [Range(65, 70), Range(80, 85)]);
+    assert_eq!(language.auxiliary, [Range(71, 79), Range(90, 90)]);
+
+    let language = parse_language(fixture("no-native-name/xx")).unwrap();
+    assert!(language.auxiliary.is_empty());
+}
+
+#[test]
+fn it_subtracts_ranges() {
+    let ranges = [Range(1, 10), Range(20, 30), Range(40, 40)];
+    let other = [Range(0, 2), Range(5, 6), Range(10, 22), Range(40, 40)];
+    assert_eq!(
+        subtract(&ranges, &other),
+        [Range(3, 4), Range(7, 9), Range(23, 30)]
+    );
+    assert_eq!(subtract(&ranges, &[]), ranges);
+    assert_eq!(
+        subtract(&[Range(0, u32::MAX)], &[Range(5, u32::MAX)]),
+        [Range(0, 4)]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn it_loads_the_ranges_of_every_language() {
+    let matches = unicode_language::detect([[0, 0x10FFFF]], 1.0);
+    let languages = unicode_language::languages().filter(|l| l.total > 0);
+    assert_eq!(matches.len(), languages.count());
+}